@@ -0,0 +1,268 @@
+use crate::backend::Backend;
+use crate::cli::{Commands, Remove};
+use crate::cmd::Cmd;
+use crate::error::{ProcessError, ScriptError};
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde::Deserialize;
+use serde_json::{json, Value as Json};
+use std::process;
+
+/// The json printed by alerter.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Output {
+    /// actionClicked, contentsClicked, replied, closed or timeout.
+    activation_type: String,
+    /// The label of the action, the text replied or the close label.
+    activation_value: Option<String>,
+}
+
+/// Read the json printed by alerter as the record of the notification.
+fn record(stdout: &str) -> Result<Json> {
+    let x: Output = serde_json::from_str(stdout)
+        .map_err(|err| anyhow!("invalid alerter output {stdout:?}: {err}"))?;
+    let mut record = json!({ "activation type": x.activation_type });
+    let key = match x.activation_type.as_str() {
+        "actionClicked" => "action",
+        "replied" => "reply",
+        _ => return Ok(record),
+    };
+    if let Some(v) = x.activation_value {
+        record[key] = v.into();
+    }
+    Ok(record)
+}
+
+/// Posts notifications with actions or a reply field by alerter, and waits for the user.
+///
+/// See https://github.com/vjeantet/alerter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alerter {
+    program: String,
+}
+
+impl Default for Alerter {
+    fn default() -> Self {
+        Alerter {
+            program: "alerter".into(),
+        }
+    }
+}
+
+impl Alerter {
+    /// alerter command.
+    pub fn program<S: Into<String>>(mut self, x: S) -> Self {
+        self.program = x.into();
+        self
+    }
+
+    pub fn cmd(&self, command: &Commands) -> Result<Cmd> {
+        let Commands::Notification {
+            text,
+            title,
+            subtitle,
+            sound_name,
+            actions,
+            close_label,
+            reply,
+            timeout,
+            group,
+            app_icon,
+            remove,
+            ..
+        } = command
+        else {
+            return Err(anyhow!("alerter backend supports only notification"));
+        };
+        let mut c = Cmd::new(&self.program);
+        if let Some(Remove::Remove { group }) = remove {
+            c.pair("-remove", Some(group));
+            return Ok(c);
+        }
+        c.arg(Some("-json"));
+        c.pair("-message", Some(text));
+        c.pair("-title", title.as_deref());
+        c.pair("-subtitle", subtitle.as_deref());
+        c.pair("-sound", sound_name.as_deref());
+        if reply.is_some() && !actions.is_empty() {
+            return Err(anyhow!("alerter cannot take both actions and a reply"));
+        }
+        // alerter splits the actions by commas
+        if let Some(x) = actions.iter().find(|x| x.contains(',')) {
+            return Err(anyhow!(
+                "alerter cannot take an action containing commas: {x:?}"
+            ));
+        }
+        c.pair("-reply", reply.as_deref());
+        if !actions.is_empty() {
+            c.pair("-actions", Some(actions.join(",")));
+        }
+        c.pair("-closeLabel", close_label.as_deref());
+        c.pair("-timeout", timeout.map(|x| x.to_string()));
+        c.pair("-group", group.as_deref());
+        c.pair("-appIcon", app_icon.as_deref());
+        Ok(c)
+    }
+}
+
+impl Backend for Alerter {
+    /// Run the command.
+    ///
+    /// If alerter fails, the error is [ProcessError].
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let c = self.cmd(command)?;
+        debug!("cmd: {c:?}");
+
+        let out = process::Command::from(c)
+            .stdin(process::Stdio::null())
+            .output()?;
+        let stdout = String::from_utf8(out.stdout)?;
+        if !out.status.success() {
+            let code = out.status.code();
+            let message = match String::from_utf8_lossy(&out.stderr).trim() {
+                "" => format!("exit status {code:?}"),
+                x => x.to_string(),
+            };
+            return Err(ProcessError {
+                code,
+                error: ScriptError::unknown(message),
+            }
+            .into());
+        }
+        let record = match command {
+            Commands::Notification {
+                remove: Some(_), ..
+            } => Json::Null,
+            _ => record(&stdout)?,
+        };
+        command.data(stdout, record)
+    }
+    fn removes_notifications(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(actions: &[&str], reply: Option<&str>) -> Commands {
+        Commands::Notification {
+            text: "t".into(),
+            title: Some("x".into()),
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: actions.iter().map(|x| x.to_string()).collect(),
+            close_label: Some("Later".into()),
+            reply: reply.map(String::from),
+            timeout: Some(30),
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: None,
+        }
+    }
+
+    fn args(x: Cmd) -> Vec<String> {
+        process::Command::from(x)
+            .get_args()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    macro_rules! test_cmd {
+        ($name:ident, $command:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = Alerter::default().cmd(&$command).unwrap();
+                assert_eq!($want, args(got));
+            }
+        };
+    }
+
+    test_cmd!(
+        test_cmd_actions,
+        notification(&["Yes", "No"], None),
+        vec![
+            "-json",
+            "-message",
+            "t",
+            "-title",
+            "x",
+            "-actions",
+            "Yes,No",
+            "-closeLabel",
+            "Later",
+            "-timeout",
+            "30",
+        ]
+    );
+    test_cmd!(
+        test_cmd_reply,
+        notification(&[], Some("")),
+        vec![
+            "-json",
+            "-message",
+            "t",
+            "-title",
+            "x",
+            "-reply",
+            "",
+            "-closeLabel",
+            "Later",
+            "-timeout",
+            "30",
+        ]
+    );
+
+    macro_rules! test_record {
+        ($name:ident, $stdout:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!($want, record($stdout).unwrap());
+            }
+        };
+    }
+
+    test_record!(
+        test_record_action,
+        r#"{"activationType":"actionClicked","activationValue":"Yes","activationValueIndex":"0","deliveredAt":"2024-01-01 00:00:00 +0000"}"#,
+        json!({"activation type": "actionClicked", "action": "Yes"})
+    );
+    test_record!(
+        test_record_replied,
+        r#"{"activationType":"replied","activationValue":"LGTM"}"#,
+        json!({"activation type": "replied", "reply": "LGTM"})
+    );
+    test_record!(
+        test_record_closed,
+        r#"{"activationType":"closed","activationValue":"Later"}"#,
+        json!({"activation type": "closed"})
+    );
+    test_record!(
+        test_record_timeout,
+        r#"{"activationType":"timeout"}"#,
+        json!({"activation type": "timeout"})
+    );
+
+    #[test]
+    fn test_cmd_invalid_actions() {
+        let alerter = Alerter::default();
+        assert!(alerter.cmd(&notification(&["Yes"], Some(""))).is_err());
+        assert!(alerter.cmd(&notification(&["Yes, deploy"], None)).is_err());
+    }
+
+    #[test]
+    fn test_run_invalid_output() {
+        let got = Alerter::default()
+            .program("echo")
+            .run(&notification(&[], None));
+        assert!(got.is_err());
+    }
+}
@@ -0,0 +1,306 @@
+use crate::backend::linux::summary;
+use crate::backend::Backend;
+use crate::cli::{Commands, Urgency};
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde_json::{json, Value as Json};
+use std::collections::HashMap;
+use zbus::blocking::{connection, proxy, Connection, Proxy};
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::Value;
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+impl From<Urgency> for u8 {
+    fn from(from: Urgency) -> Self {
+        match from {
+            Urgency::Low => 0,
+            Urgency::Normal => 1,
+            Urgency::Critical => 2,
+        }
+    }
+}
+
+/// Posts notifications to org.freedesktop.Notifications on the session bus.
+///
+/// See https://specifications.freedesktop.org/notification-spec/latest/
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Dbus {
+    address: Option<String>,
+}
+
+impl Dbus {
+    /// Address of the bus.
+    /// Default: the session bus.
+    pub fn address<S: Into<String>>(mut self, x: S) -> Self {
+        self.address = Some(x.into());
+        self
+    }
+
+    fn connect(&self) -> Result<Connection> {
+        Ok(match &self.address {
+            Some(x) => connection::Builder::address(x.as_str())?.build()?,
+            None => Connection::session()?,
+        })
+    }
+
+    /// Post the notification and wait for the action if there are actions.
+    ///
+    /// The record has the activation type and the action.
+    fn notify(&self, command: &Commands) -> Result<Json> {
+        let Commands::Notification {
+            text,
+            title,
+            subtitle,
+            sound_name,
+            urgency,
+            expire_timeout,
+            actions,
+            remove: None,
+            ..
+        } = command
+        else {
+            return Err(anyhow!("dbus backend supports only posting notifications"));
+        };
+        let (summary, body) = summary(text, title.as_deref(), subtitle.as_deref());
+        let mut hints: HashMap<&str, Value> = HashMap::new();
+        if let Some(x) = urgency {
+            hints.insert("urgency", u8::from(*x).into());
+        }
+        if let Some(x) = sound_name {
+            hints.insert("sound-name", x.as_str().into());
+        }
+        // keyed by the index, since "default" is reserved for clicking the notification
+        let keys: Vec<String> = (0..actions.len()).map(|i| format!("action-{i}")).collect();
+        let xs: Vec<&str> = keys
+            .iter()
+            .zip(actions)
+            .flat_map(|(k, x)| [k.as_str(), x])
+            .collect();
+
+        let conn = self.connect()?;
+        let proxy: Proxy = proxy::Builder::new(&conn)
+            .destination(DESTINATION)?
+            .path(PATH)?
+            .interface(INTERFACE)?
+            .cache_properties(CacheProperties::No)
+            .build()?;
+        // subscribe before posting not to miss the signals
+        let signals = if actions.is_empty() {
+            None
+        } else {
+            Some(proxy.receive_all_signals()?)
+        };
+        let id: u32 = proxy.call(
+            "Notify",
+            &(
+                "scptdisplay",
+                0u32,
+                "",
+                summary,
+                body.unwrap_or_default(),
+                xs,
+                hints,
+                expire_timeout.unwrap_or(-1),
+            ),
+        )?;
+        debug!("notification id: {id}");
+        let Some(signals) = signals else {
+            return Ok(json!({}));
+        };
+        for msg in signals {
+            let header = msg.header();
+            match header.member().map(|x| x.as_str()) {
+                Some("ActionInvoked") => {
+                    let (x, key): (u32, String) = msg.body().deserialize()?;
+                    if x != id {
+                        continue;
+                    }
+                    return Ok(match keys.iter().position(|k| *k == key) {
+                        Some(i) => {
+                            json!({"activation type": "actionClicked", "action": actions[i]})
+                        }
+                        None => json!({ "activation type": "contentsClicked" }),
+                    });
+                }
+                Some("NotificationClosed") => {
+                    let (x, reason): (u32, u32) = msg.body().deserialize()?;
+                    if x == id {
+                        debug!("notification closed: {reason}");
+                        // 1 means expired
+                        let t = if reason == 1 { "timeout" } else { "closed" };
+                        return Ok(json!({ "activation type": t }));
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(anyhow!(
+            "connection closed before the notification is closed"
+        ))
+    }
+}
+
+impl Backend for Dbus {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let record = self.notify(command)?;
+        command.data(record.to_string(), record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::find_program;
+    use std::io::{BufRead, BufReader};
+    use std::process;
+    use std::sync::mpsc;
+    use std::thread;
+    use zbus::blocking::MessageIterator;
+    use zbus::message::Type;
+    use zbus::zvariant::OwnedValue;
+
+    /// A private session bus.
+    struct Daemon(process::Child);
+
+    impl Daemon {
+        fn start() -> Option<(Daemon, String)> {
+            find_program("dbus-daemon")?;
+            let mut child = process::Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(process::Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(child.stdout.take().unwrap())
+                .read_line(&mut address)
+                .ok()?;
+            Some((Daemon(child), address.trim().to_string()))
+        }
+    }
+
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    type Notify = (
+        String,
+        u32,
+        String,
+        String,
+        String,
+        Vec<String>,
+        HashMap<String, OwnedValue>,
+        i32,
+    );
+
+    /// Serve Notify and invoke the first action if any.
+    fn serve(address: &str) -> mpsc::Receiver<Notify> {
+        let conn = connection::Builder::address(address)
+            .unwrap()
+            .build()
+            .unwrap();
+        conn.request_name(DESTINATION).unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            // the iterator fails when the daemon stops
+            for msg in MessageIterator::from(&conn).map_while(|x| x.ok()) {
+                let header = msg.header();
+                if header.message_type() != Type::MethodCall
+                    || header.member().map(|x| x.as_str()) != Some("Notify")
+                {
+                    continue;
+                }
+                let x: Notify = msg.body().deserialize().unwrap();
+                conn.reply(&header, &7u32).unwrap();
+                if let Some(key) = x.5.first() {
+                    let body = (7u32, key.as_str());
+                    conn.emit_signal(None::<&str>, PATH, INTERFACE, "ActionInvoked", &body)
+                        .unwrap();
+                }
+                if tx.send(x).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    fn notification(actions: Vec<String>) -> Commands {
+        Commands::Notification {
+            text: "t".into(),
+            title: Some("x".into()),
+            subtitle: None,
+            sound_name: Some("bell".into()),
+            urgency: Some(Urgency::Critical),
+            expire_timeout: Some(0),
+            actions,
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: None,
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let Some((_daemon, address)) = Daemon::start() else {
+            eprintln!("skip: dbus-daemon is not found");
+            return;
+        };
+        let rx = serve(&address);
+        let dbus = Dbus::default().address(&address);
+
+        let got = dbus
+            .run(&notification(vec!["Yes".into(), "No".into()]))
+            .unwrap();
+        assert_eq!(
+            Data::Notification {
+                activation_type: Some("actionClicked".into()),
+                action: Some("Yes".into()),
+                reply: None,
+            },
+            got
+        );
+        let (app, _, _, summary, body, actions, hints, timeout) = rx.recv().unwrap();
+        assert_eq!("scptdisplay", app);
+        assert_eq!("x", summary);
+        assert_eq!("t", body);
+        assert_eq!(vec!["action-0", "Yes", "action-1", "No"], actions);
+        assert_eq!(Value::U8(2), *hints["urgency"]);
+        assert_eq!(Value::from("bell"), *hints["sound-name"]);
+        assert_eq!(0, timeout);
+
+        let got = dbus.run(&notification(vec![])).unwrap();
+        assert_eq!(
+            Data::Notification {
+                activation_type: None,
+                action: None,
+                reply: None
+            },
+            got
+        );
+        rx.recv().unwrap();
+
+        let got = dbus.run(&notification(vec!["default".into()])).unwrap();
+        assert_eq!(
+            Data::Notification {
+                activation_type: Some("actionClicked".into()),
+                action: Some("default".into()),
+                reply: None,
+            },
+            got
+        );
+    }
+}
@@ -0,0 +1,1202 @@
+use crate::backend::{find_program, Backend};
+use crate::cli::{AlertType, Commands, Urgency};
+use crate::cmd::Cmd;
+use crate::color::Color;
+use crate::error::{ProcessError, ScriptError};
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use log::debug;
+use serde_json::{json, Value as Json};
+use std::env;
+use std::path::Path;
+use std::process;
+
+/// A program that displays alerts and dialogs.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Tool {
+    Zenity,
+    Kdialog,
+}
+
+impl Tool {
+    fn program(&self) -> &'static str {
+        match self {
+            Tool::Zenity => "zenity",
+            Tool::Kdialog => "kdialog",
+        }
+    }
+
+    /// kdialog on KDE and zenity otherwise, or the other one if it is not installed.
+    fn detect() -> Option<Tool> {
+        let kde = env::var("XDG_CURRENT_DESKTOP").is_ok_and(|x| x.contains("KDE"));
+        let xs = if kde {
+            [Tool::Kdialog, Tool::Zenity]
+        } else {
+            [Tool::Zenity, Tool::Kdialog]
+        };
+        xs.into_iter().find(|x| find_program(x.program()).is_some())
+    }
+}
+
+/// The kind of the message, instead of the icon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Style {
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<AlertType> for Style {
+    fn from(from: AlertType) -> Self {
+        match from {
+            AlertType::Informational => Style::Info,
+            AlertType::Warning => Style::Warning,
+            AlertType::Critical => Style::Error,
+        }
+    }
+}
+
+impl Style {
+    fn from_icon(x: Option<&str>) -> Style {
+        match x {
+            Some("stop" | "0") => Style::Error,
+            Some("caution" | "2") => Style::Warning,
+            _ => Style::Info,
+        }
+    }
+}
+
+/// An alert or a dialog independent of the tool.
+#[derive(Debug)]
+struct Spec<'a> {
+    text: String,
+    title: Option<&'a str>,
+    style: Style,
+    /// The default answer and whether it is hidden, present if there is an edit field.
+    answer: Option<(&'a str, bool)>,
+    /// Buttons from left to right, at least one.
+    buttons: Vec<String>,
+    cancel: Option<String>,
+    icon_file: Option<&'a str>,
+    giving_up_after: Option<u8>,
+}
+
+/// The buttons and the name of the cancel button.
+///
+/// `defaults` and `default_cancel` are used when no buttons are specified.
+pub(crate) fn buttons(
+    buttons: &[String],
+    cancel: Option<&str>,
+    defaults: &[&str],
+    default_cancel: Option<&str>,
+) -> (Vec<String>, Option<String>) {
+    let (buttons, cancel) = if buttons.is_empty() {
+        let xs = defaults.iter().map(|x| x.to_string()).collect();
+        (xs, cancel.or(default_cancel))
+    } else {
+        (buttons.to_vec(), cancel)
+    };
+    let cancel = cancel.map(|x| match x.parse::<usize>() {
+        Ok(n) => buttons.get(n.wrapping_sub(1)).cloned().unwrap_or_default(),
+        Err(_) => x.to_string(),
+    });
+    (buttons, cancel)
+}
+
+/// The summary and the body of the notification.
+pub(crate) fn summary(
+    text: &str,
+    title: Option<&str>,
+    subtitle: Option<&str>,
+) -> (String, Option<String>) {
+    match (title, subtitle) {
+        (Some(t), Some(s)) => (t.into(), Some(format!("{s}\n{text}"))),
+        (Some(t), None) | (None, Some(t)) => (t.into(), Some(text.into())),
+        (None, None) => (text.into(), None),
+    }
+}
+
+/// A program to run and how to read the result from its exit status and stdout.
+#[derive(Debug, PartialEq)]
+struct Invocation {
+    cmd: Cmd,
+    /// Buttons returned by the exit status 0, 1 and 2.
+    /// Empty if the program has no buttons, then it succeeds only by 0.
+    statuses: Vec<String>,
+    /// Buttons that print their names when pressed.
+    printed: Vec<String>,
+    cancel: Option<String>,
+    /// True if stdout is the text entered.
+    answer: bool,
+    /// The exit status when the program gave up.
+    timeout: Option<i32>,
+    /// Present if stdout is the lines chosen.
+    lines: Option<Lines>,
+}
+
+/// What the lines printed by the program are.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Lines {
+    /// Items of choose from list, which returns false if cancelled.
+    Items,
+    /// Paths of files.
+    Files,
+    /// Paths of folders, which end with a slash as POSIX path of the folder alias.
+    Folders,
+    /// A color as #rrggbb or rgb(r,g,b), read as the 16-bit components of apple script.
+    Color,
+}
+
+impl Invocation {
+    fn new(cmd: Cmd) -> Invocation {
+        Invocation {
+            cmd,
+            statuses: vec![],
+            printed: vec![],
+            cancel: None,
+            answer: false,
+            timeout: None,
+            lines: None,
+        }
+    }
+
+    /// Read the result as a record of display dialog, or a list of the lines chosen.
+    fn record(&self, code: Option<i32>, stdout: &str, stderr: &str) -> Result<Json> {
+        let failed = || -> anyhow::Error {
+            let message = match stderr.trim() {
+                "" => format!("exit status {code:?}"),
+                x => x.to_string(),
+            };
+            ProcessError {
+                code,
+                error: ScriptError::unknown(message),
+            }
+            .into()
+        };
+        let cancelled = || -> anyhow::Error {
+            ProcessError {
+                code,
+                error: ScriptError::new("User canceled.", Some(-128)),
+            }
+            .into()
+        };
+
+        if let Some(lines) = self.lines {
+            return match (code, lines) {
+                (Some(0), Lines::Color) => Ok(json!(Color::parse_css(stdout)?.0)),
+                (Some(0), Lines::Folders) => Ok(stdout
+                    .lines()
+                    .map(|x| format!("{}/", x.trim_end_matches('/')))
+                    .collect::<Vec<_>>()
+                    .into()),
+                (Some(0), _) => Ok(stdout.lines().collect::<Vec<_>>().into()),
+                // the cancel button or closed
+                (Some(1), Lines::Items) => Ok(false.into()),
+                (Some(1), _) => Err(cancelled()),
+                _ => Err(failed()),
+            };
+        }
+        if self.statuses.is_empty() {
+            return if code == Some(0) {
+                Ok(Json::Null)
+            } else {
+                Err(failed())
+            };
+        }
+        let out = stdout.strip_suffix('\n').unwrap_or(stdout);
+        let mut record = json!({});
+        if code.is_some() && code == self.timeout {
+            record["button returned"] = "".into();
+            record["gave up"] = true.into();
+            return Ok(record);
+        }
+        let button = if code != Some(0) && self.printed.iter().any(|x| x == out) {
+            out.to_string()
+        } else {
+            match code.and_then(|x| usize::try_from(x).ok()) {
+                Some(i) if i < self.statuses.len() => self.statuses[i].clone(),
+                // closed without pressing any buttons
+                Some(1) => return Err(cancelled()),
+                _ => return Err(failed()),
+            }
+        };
+        if self.cancel.as_ref() == Some(&button) {
+            return Err(cancelled());
+        }
+        record["button returned"] = button.into();
+        if self.answer {
+            record["text returned"] = if code == Some(0) { out } else { "" }.into();
+        }
+        if self.timeout.is_some() {
+            record["gave up"] = false.into();
+        }
+        Ok(record)
+    }
+}
+
+fn notify_send(
+    summary: String,
+    body: Option<String>,
+    sound_name: Option<&str>,
+    urgency: Option<Urgency>,
+    expire_timeout: Option<i32>,
+) -> Invocation {
+    let mut c = Cmd::new("notify-send");
+    c.pair(
+        "--urgency",
+        urgency.map(|x| x.to_possible_value().unwrap().get_name().to_string()),
+    );
+    c.pair("--expire-time", expire_timeout.map(|x| x.to_string()));
+    c.pair(
+        "--hint",
+        sound_name.map(|x| format!("string:sound-name:{x}")),
+    );
+    c.arg(Some("--"));
+    c.arg(Some(summary));
+    c.arg(body);
+    Invocation::new(c)
+}
+
+fn zenity_notification(summary: String, body: Option<String>) -> Invocation {
+    let text = match body {
+        Some(x) => format!("{summary}\n{x}"),
+        None => summary,
+    };
+    let mut c = Cmd::new("zenity");
+    c.arg(Some("--notification"));
+    c.arg(Some(format!("--text={text}")));
+    Invocation::new(c)
+}
+
+fn kdialog_notification(summary: String, body: Option<String>) -> Invocation {
+    let mut c = Cmd::new("kdialog");
+    match body {
+        Some(x) => {
+            c.pair("--title", Some(summary));
+            c.pair("--passivepopup", Some(x));
+        }
+        None => c.pair("--passivepopup", Some(summary)),
+    }
+    // seconds to show the popup
+    c.arg(Some("5"));
+    Invocation::new(c)
+}
+
+fn zenity(x: Spec) -> Invocation {
+    let mut c = Cmd::new("zenity");
+    if let Some((default_answer, hidden)) = x.answer {
+        c.arg(Some("--entry"));
+        c.arg(Some(format!("--text={}", x.text)));
+        c.arg(Some(format!("--entry-text={default_answer}")));
+        c.arg(hidden.then_some("--hide-text"));
+    } else {
+        c.arg(Some(match (x.buttons.len(), x.style) {
+            (1, Style::Info) => "--info",
+            (1, Style::Warning) => "--warning",
+            (1, Style::Error) => "--error",
+            _ => "--question",
+        }));
+        c.arg(Some("--no-markup"));
+        c.arg(Some(format!("--text={}", x.text)));
+    }
+    c.arg(x.title.map(|t| format!("--title={t}")));
+    c.arg(x.icon_file.map(|t| format!("--window-icon={t}")));
+    c.arg(x.giving_up_after.map(|t| format!("--timeout={t}")));
+    // the rightmost button is ok, the next is cancel if it is the cancel button and the others
+    // are extra buttons, so that exit status 1 without output means the window was closed
+    let mut xs = x.buttons;
+    let mut statuses = vec![xs.pop().unwrap()];
+    c.arg(Some(format!("--ok-label={}", statuses[0])));
+    if xs.last().is_some() && xs.last() == x.cancel.as_ref() {
+        let b = xs.pop().unwrap();
+        c.arg(Some(format!("--cancel-label={b}")));
+        statuses.push(b);
+    }
+    for b in &xs {
+        c.arg(Some(format!("--extra-button={b}")));
+    }
+    Invocation {
+        cmd: c,
+        statuses,
+        printed: xs,
+        cancel: x.cancel,
+        answer: x.answer.is_some(),
+        timeout: x.giving_up_after.map(|_| 5),
+        lines: None,
+    }
+}
+
+fn kdialog(x: Spec) -> Invocation {
+    let mut c = Cmd::new("kdialog");
+    c.pair("--title", x.title);
+    c.pair("--icon", x.icon_file);
+    let mut xs = x.buttons;
+    let statuses: Vec<String> = if let Some((default_answer, hidden)) = x.answer {
+        if hidden {
+            c.pair("--password", Some(x.text));
+        } else {
+            c.pair("--inputbox", Some(x.text));
+            c.arg(Some(default_answer));
+        }
+        // only ok and cancel
+        let mut statuses = vec![xs.pop().unwrap()];
+        c.pair("--ok-label", Some(&statuses[0]));
+        if let Some(b) = xs.pop() {
+            c.pair("--cancel-label", Some(&b));
+            statuses.push(b);
+        }
+        statuses
+    } else {
+        xs.reverse();
+        match xs.as_slice() {
+            [ok] => {
+                let option = match x.style {
+                    Style::Info => "--msgbox",
+                    Style::Warning => "--sorry",
+                    Style::Error => "--error",
+                };
+                c.pair(option, Some(x.text));
+                c.pair("--ok-label", Some(ok));
+            }
+            [yes, no] => {
+                c.pair("--yesno", Some(x.text));
+                c.pair("--yes-label", Some(yes));
+                c.pair("--no-label", Some(no));
+            }
+            [yes, no, cancel, ..] => {
+                c.pair("--yesnocancel", Some(x.text));
+                c.pair("--yes-label", Some(yes));
+                c.pair("--no-label", Some(no));
+                c.pair("--cancel-label", Some(cancel));
+            }
+            [] => unreachable!("at least one button"),
+        }
+        xs.truncate(3);
+        xs
+    };
+    if x.giving_up_after.is_some() {
+        debug!("kdialog cannot give up, ignore giving_up_after");
+    }
+    Invocation {
+        cmd: c,
+        statuses,
+        printed: vec![],
+        cancel: x.cancel,
+        answer: x.answer.is_some(),
+        timeout: None,
+        lines: None,
+    }
+}
+
+/// A list to choose items from, independent of the tool.
+#[derive(Debug)]
+struct ListSpec<'a> {
+    items: &'a [String],
+    prompt: &'a str,
+    title: Option<&'a str>,
+    default_items: &'a [String],
+    ok: Option<&'a str>,
+    cancel: Option<&'a str>,
+    multiple: bool,
+}
+
+impl ListSpec<'_> {
+    fn is_default(&self, x: &str) -> bool {
+        self.default_items.iter().any(|d| d == x)
+    }
+}
+
+/// Items are toggled in a check list if multiple, selected by a radio list if there are default items.
+fn zenity_list(x: ListSpec) -> Invocation {
+    let mut c = Cmd::new("zenity");
+    c.arg(Some("--list"));
+    c.arg(Some(format!("--text={}", x.prompt)));
+    c.arg(x.title.map(|t| format!("--title={t}")));
+    c.arg(x.ok.map(|t| format!("--ok-label={t}")));
+    c.arg(x.cancel.map(|t| format!("--cancel-label={t}")));
+    c.arg(Some("--hide-header"));
+    c.arg(Some("--separator=\n"));
+    let toggle = if x.multiple {
+        Some("--checklist")
+    } else if !x.default_items.is_empty() {
+        Some("--radiolist")
+    } else {
+        None
+    };
+    match toggle {
+        Some(t) => {
+            c.arg(Some(t));
+            c.arg(Some("--column="));
+            c.arg(Some("--column=Item"));
+            c.arg(Some("--print-column=2"));
+            for i in x.items {
+                c.arg(Some(if x.is_default(i) { "TRUE" } else { "FALSE" }));
+                c.arg(Some(i));
+            }
+        }
+        None => {
+            c.arg(Some("--column=Item"));
+            for i in x.items {
+                c.arg(Some(i));
+            }
+        }
+    }
+    Invocation {
+        lines: Some(Lines::Items),
+        ..Invocation::new(c)
+    }
+}
+
+/// What a file chooser chooses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Chooser {
+    File,
+    Folder,
+    /// A name of a new file, a save dialog.
+    FileName,
+}
+
+/// A file or folder chooser, independent of the tool.
+#[derive(Debug)]
+struct FileSpec<'a> {
+    chooser: Chooser,
+    prompt: Option<&'a str>,
+    default_location: Option<&'a Path>,
+    default_name: Option<&'a str>,
+    multiple: bool,
+}
+
+impl FileSpec<'_> {
+    fn lines(&self) -> Lines {
+        match self.chooser {
+            Chooser::Folder => Lines::Folders,
+            _ => Lines::Files,
+        }
+    }
+    /// The path initially selected, a folder ends with a slash to browse in it.
+    fn start(&self) -> Option<String> {
+        let folder = self
+            .default_location
+            .map(|x| format!("{}/", x.to_string_lossy().trim_end_matches('/')));
+        match (folder, self.default_name) {
+            (Some(x), Some(name)) => Some(format!("{x}{name}")),
+            (None, Some(name)) => Some(name.into()),
+            (x, None) => x,
+        }
+    }
+}
+
+fn zenity_file(x: FileSpec) -> Invocation {
+    let mut c = Cmd::new("zenity");
+    c.arg(Some("--file-selection"));
+    c.arg(x.prompt.map(|t| format!("--title={t}")));
+    c.arg(match x.chooser {
+        Chooser::File => None,
+        Chooser::Folder => Some("--directory"),
+        Chooser::FileName => Some("--save"),
+    });
+    if x.multiple {
+        c.arg(Some("--multiple"));
+        c.arg(Some("--separator=\n"));
+    }
+    c.arg(x.start().map(|t| format!("--filename={t}")));
+    Invocation {
+        lines: Some(x.lines()),
+        ..Invocation::new(c)
+    }
+}
+
+/// kdialog cannot choose multiple folders.
+fn kdialog_file(x: FileSpec) -> Invocation {
+    let mut c = Cmd::new("kdialog");
+    c.pair("--title", x.prompt);
+    match x.chooser {
+        Chooser::File => {
+            if x.multiple {
+                c.arg(Some("--multiple"));
+                c.arg(Some("--separate-output"));
+            }
+            c.arg(Some("--getopenfilename"));
+        }
+        Chooser::Folder => c.arg(Some("--getexistingdirectory")),
+        Chooser::FileName => c.arg(Some("--getsavefilename")),
+    }
+    if x.chooser == Chooser::FileName {
+        c.arg(x.start());
+    } else {
+        c.arg(x.default_location.map(|t| t.to_string_lossy()));
+    }
+    Invocation {
+        lines: Some(x.lines()),
+        ..Invocation::new(c)
+    }
+}
+
+/// The items are the tags printed by kdialog.
+fn kdialog_list(x: ListSpec) -> Invocation {
+    let mut c = Cmd::new("kdialog");
+    c.pair("--title", x.title);
+    c.pair("--ok-label", x.ok);
+    c.pair("--cancel-label", x.cancel);
+    let toggle = if x.multiple {
+        c.arg(Some("--separate-output"));
+        Some("--checklist")
+    } else if !x.default_items.is_empty() {
+        Some("--radiolist")
+    } else {
+        None
+    };
+    c.pair(toggle.unwrap_or("--menu"), Some(x.prompt));
+    for i in x.items {
+        c.arg(Some(i));
+        c.arg(Some(i));
+        if toggle.is_some() {
+            c.arg(Some(if x.is_default(i) { "on" } else { "off" }));
+        }
+    }
+    Invocation {
+        lines: Some(Lines::Items),
+        ..Invocation::new(c)
+    }
+}
+
+/// Runs commands by notify-send, zenity or kdialog.
+///
+/// Notifications are posted by notify-send if it is installed.
+/// The rightmost button is always the default button.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Linux {
+    tool: Option<Tool>,
+}
+
+impl Linux {
+    /// The program to display alerts and dialogs.
+    /// Default: detected by the desktop and the installed programs.
+    pub fn tool(mut self, x: Option<Tool>) -> Self {
+        self.tool = x;
+        self
+    }
+
+    fn invocation(&self, command: &Commands) -> Result<Invocation> {
+        let tool = || {
+            self.tool
+                .or_else(Tool::detect)
+                .ok_or_else(|| anyhow!("neither zenity nor kdialog is found"))
+        };
+        if let Commands::Notification { .. } = command {
+            if find_program("notify-send").is_some() {
+                return build(command, None);
+            }
+        }
+        build(command, Some(tool()?))
+    }
+}
+
+/// Build the invocation of the command, notify-send is used for notifications if tool is None.
+///
+/// Actions of notifications are not supported.
+fn build(command: &Commands, tool: Option<Tool>) -> Result<Invocation> {
+    let spec = match command {
+        Commands::Notification {
+            text,
+            title,
+            subtitle,
+            sound_name,
+            urgency,
+            expire_timeout,
+            ..
+        } => {
+            let (summary, body) = summary(text, title.as_deref(), subtitle.as_deref());
+            return Ok(match tool {
+                None => notify_send(
+                    summary,
+                    body,
+                    sound_name.as_deref(),
+                    *urgency,
+                    *expire_timeout,
+                ),
+                Some(Tool::Zenity) => zenity_notification(summary, body),
+                Some(Tool::Kdialog) => kdialog_notification(summary, body),
+            });
+        }
+        Commands::Alert {
+            text,
+            message,
+            alert_type,
+            buttons: xs,
+            cancel_button,
+            giving_up_after,
+            ..
+        } => {
+            let (buttons, cancel) = buttons(xs, cancel_button.as_deref(), &["OK"], None);
+            Spec {
+                text: match message {
+                    Some(m) => format!("{text}\n\n{m}"),
+                    None => text.clone(),
+                },
+                title: None,
+                style: alert_type.map(Style::from).unwrap_or(Style::Info),
+                answer: None,
+                buttons,
+                cancel,
+                icon_file: None,
+                giving_up_after: *giving_up_after,
+            }
+        }
+        Commands::Dialog {
+            text,
+            default_answer,
+            hidden_answer,
+            buttons: xs,
+            cancel_button,
+            title,
+            icon,
+            giving_up_after,
+            ..
+        } => {
+            let (buttons, cancel) = buttons(
+                xs,
+                cancel_button.as_deref(),
+                &["Cancel", "OK"],
+                Some("Cancel"),
+            );
+            Spec {
+                text: text.clone(),
+                title: title.as_deref(),
+                style: Style::from_icon(icon.as_deref()),
+                answer: default_answer.as_deref().map(|x| (x, *hidden_answer)),
+                buttons,
+                cancel,
+                icon_file: icon.as_deref().filter(|x| x.starts_with('/')),
+                giving_up_after: *giving_up_after,
+            }
+        }
+        Commands::ChooseFromList {
+            items,
+            prompt,
+            title,
+            default_items,
+            ok_button_name,
+            cancel_button_name,
+            multiple_selections_allowed,
+            ..
+        } => {
+            let spec = ListSpec {
+                items,
+                prompt: prompt.as_deref().unwrap_or("Please make your selection:"),
+                title: title.as_deref(),
+                default_items,
+                ok: ok_button_name.as_deref(),
+                cancel: cancel_button_name.as_deref(),
+                multiple: *multiple_selections_allowed,
+            };
+            return Ok(match tool.unwrap_or(Tool::Zenity) {
+                Tool::Zenity => zenity_list(spec),
+                Tool::Kdialog => kdialog_list(spec),
+            });
+        }
+        Commands::ChooseFile {
+            prompt,
+            default_location,
+            multiple_selections_allowed,
+            ..
+        }
+        | Commands::ChooseFolder {
+            prompt,
+            default_location,
+            multiple_selections_allowed,
+            ..
+        } => {
+            let chooser = match command {
+                Commands::ChooseFolder { .. } => Chooser::Folder,
+                _ => Chooser::File,
+            };
+            let spec = FileSpec {
+                chooser,
+                prompt: prompt.as_deref(),
+                default_location: default_location.as_deref(),
+                default_name: None,
+                multiple: *multiple_selections_allowed,
+            };
+            return Ok(match tool.unwrap_or(Tool::Zenity) {
+                Tool::Zenity => zenity_file(spec),
+                Tool::Kdialog => kdialog_file(spec),
+            });
+        }
+        Commands::ChooseFileName {
+            prompt,
+            default_name,
+            default_location,
+        } => {
+            let spec = FileSpec {
+                chooser: Chooser::FileName,
+                prompt: prompt.as_deref(),
+                default_location: default_location.as_deref(),
+                default_name: default_name.as_deref(),
+                multiple: false,
+            };
+            return Ok(match tool.unwrap_or(Tool::Zenity) {
+                Tool::Zenity => zenity_file(spec),
+                Tool::Kdialog => kdialog_file(spec),
+            });
+        }
+        Commands::ChooseColor { default } => {
+            let mut c = Cmd::new(tool.unwrap_or(Tool::Zenity).program());
+            match tool.unwrap_or(Tool::Zenity) {
+                Tool::Zenity => {
+                    c.arg(Some("--color-selection"));
+                    c.arg(default.map(|x| format!("--color={x}")));
+                }
+                Tool::Kdialog => {
+                    c.arg(Some("--getcolor"));
+                    c.pair("--default", default.map(|x| x.hex()));
+                }
+            }
+            return Ok(Invocation {
+                lines: Some(Lines::Color),
+                ..Invocation::new(c)
+            });
+        }
+        Commands::Say { .. } | Commands::Voices => {
+            return Err(anyhow!("linux backend cannot run {}", command.kind()))
+        }
+        Commands::Pick { .. } => {
+            return Err(anyhow!(
+                "pick runs choose from list, convert it by Lines::command"
+            ))
+        }
+        Commands::Schema => return Err(anyhow!("schema runs no script")),
+    };
+    Ok(match tool.unwrap_or(Tool::Zenity) {
+        Tool::Zenity => zenity(spec),
+        Tool::Kdialog => kdialog(spec),
+    })
+}
+
+impl Backend for Linux {
+    /// Run the command.
+    ///
+    /// If the program fails or the cancel button is pressed, the error is [ProcessError].
+    /// The cancel button of choose from list is not an error but returns cancelled as osascript does.
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let x = self.invocation(command)?;
+        debug!("cmd: {:?}", x.cmd);
+
+        let out = process::Command::from(x.cmd.clone())
+            .stdin(process::Stdio::null())
+            .output()?;
+        let stdout = String::from_utf8(out.stdout)?;
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let record = x.record(out.status.code(), &stdout, &stderr)?;
+        command.data(stdout, record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Category;
+    use std::path::PathBuf;
+
+    fn dialog(buttons: &[&str], default_answer: Option<&str>) -> Commands {
+        Commands::Dialog {
+            text: "t".into(),
+            default_answer: default_answer.map(String::from),
+            hidden_answer: false,
+            buttons: buttons.iter().map(|x| x.to_string()).collect(),
+            default_button: None,
+            cancel_button: None,
+            title: Some("x".into()),
+            icon: None,
+            giving_up_after: Some(3),
+        }
+    }
+
+    fn choose_from_list(default_items: &[&str], multiple: bool) -> Commands {
+        Commands::ChooseFromList {
+            items: vec!["a".into(), "b".into()],
+            prompt: None,
+            title: None,
+            default_items: default_items.iter().map(|x| x.to_string()).collect(),
+            ok_button_name: Some("Go".into()),
+            cancel_button_name: None,
+            multiple_selections_allowed: multiple,
+            empty_selection_allowed: false,
+        }
+    }
+
+    fn choose_folder(default_location: Option<&str>, multiple: bool) -> Commands {
+        Commands::ChooseFolder {
+            prompt: Some("p".into()),
+            default_location: default_location.map(PathBuf::from),
+            invisibles: false,
+            multiple_selections_allowed: multiple,
+            showing_package_contents: false,
+        }
+    }
+
+    fn args(x: &Invocation) -> Vec<String> {
+        process::Command::from(x.cmd.clone())
+            .get_args()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    macro_rules! test_build {
+        ($name:ident, $command:expr, $tool:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = build(&$command, $tool).unwrap();
+                assert_eq!($want, args(&got));
+            }
+        };
+    }
+
+    test_build!(
+        test_build_notify_send,
+        Commands::Notification {
+            text: "t".into(),
+            title: Some("x".into()),
+            subtitle: Some("s".into()),
+            sound_name: Some("bell".into()),
+            urgency: Some(Urgency::Critical),
+            expire_timeout: Some(0),
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: None,
+        },
+        None,
+        vec![
+            "--urgency",
+            "critical",
+            "--expire-time",
+            "0",
+            "--hint",
+            "string:sound-name:bell",
+            "--",
+            "x",
+            "s\nt"
+        ]
+    );
+    test_build!(
+        test_build_kdialog_notification,
+        Commands::Notification {
+            text: "t".into(),
+            title: None,
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: None,
+        },
+        Some(Tool::Kdialog),
+        vec!["--passivepopup", "t", "5"]
+    );
+    test_build!(
+        test_build_zenity_dialog,
+        dialog(&[], None),
+        Some(Tool::Zenity),
+        vec![
+            "--question",
+            "--no-markup",
+            "--text=t",
+            "--title=x",
+            "--timeout=3",
+            "--ok-label=OK",
+            "--cancel-label=Cancel",
+        ]
+    );
+    test_build!(
+        test_build_zenity_entry,
+        dialog(&["A", "B", "C"], Some("-d")),
+        Some(Tool::Zenity),
+        vec![
+            "--entry",
+            "--text=t",
+            "--entry-text=-d",
+            "--title=x",
+            "--timeout=3",
+            "--ok-label=C",
+            "--extra-button=A",
+            "--extra-button=B",
+        ]
+    );
+    test_build!(
+        test_build_kdialog_yesnocancel,
+        dialog(&["A", "B", "C"], None),
+        Some(Tool::Kdialog),
+        vec![
+            "--title",
+            "x",
+            "--yesnocancel",
+            "t",
+            "--yes-label",
+            "C",
+            "--no-label",
+            "B",
+            "--cancel-label",
+            "A",
+        ]
+    );
+    test_build!(
+        test_build_kdialog_alert,
+        Commands::Alert {
+            text: "t".into(),
+            message: Some("m".into()),
+            alert_type: Some(AlertType::Warning),
+            buttons: vec![],
+            default_button: None,
+            cancel_button: None,
+            giving_up_after: None,
+        },
+        Some(Tool::Kdialog),
+        vec!["--sorry", "t\n\nm", "--ok-label", "OK"]
+    );
+    test_build!(
+        test_build_zenity_list,
+        choose_from_list(&[], false),
+        Some(Tool::Zenity),
+        vec![
+            "--list",
+            "--text=Please make your selection:",
+            "--ok-label=Go",
+            "--hide-header",
+            "--separator=\n",
+            "--column=Item",
+            "a",
+            "b",
+        ]
+    );
+    test_build!(
+        test_build_zenity_checklist,
+        choose_from_list(&["b"], true),
+        Some(Tool::Zenity),
+        vec![
+            "--list",
+            "--text=Please make your selection:",
+            "--ok-label=Go",
+            "--hide-header",
+            "--separator=\n",
+            "--checklist",
+            "--column=",
+            "--column=Item",
+            "--print-column=2",
+            "FALSE",
+            "a",
+            "TRUE",
+            "b",
+        ]
+    );
+    test_build!(
+        test_build_kdialog_radiolist,
+        choose_from_list(&["a"], false),
+        Some(Tool::Kdialog),
+        vec![
+            "--ok-label",
+            "Go",
+            "--radiolist",
+            "Please make your selection:",
+            "a",
+            "a",
+            "on",
+            "b",
+            "b",
+            "off",
+        ]
+    );
+    test_build!(
+        test_build_zenity_folder,
+        choose_folder(Some("/tmp/"), true),
+        Some(Tool::Zenity),
+        vec![
+            "--file-selection",
+            "--title=p",
+            "--directory",
+            "--multiple",
+            "--separator=\n",
+            "--filename=/tmp/",
+        ]
+    );
+    test_build!(
+        test_build_zenity_file_name,
+        Commands::ChooseFileName {
+            prompt: None,
+            default_name: Some("report.csv".into()),
+            default_location: Some("/tmp/".into()),
+        },
+        Some(Tool::Zenity),
+        vec!["--file-selection", "--save", "--filename=/tmp/report.csv"]
+    );
+    test_build!(
+        test_build_zenity_color,
+        Commands::ChooseColor {
+            default: Some(Color([65535, 32896, 0])),
+        },
+        Some(Tool::Zenity),
+        vec!["--color-selection", "--color=#ff8000"]
+    );
+    test_build!(
+        test_build_kdialog_color,
+        Commands::ChooseColor {
+            default: Some(Color([65535, 32896, 0])),
+        },
+        Some(Tool::Kdialog),
+        vec!["--getcolor", "--default", "#ff8000"]
+    );
+    test_build!(
+        test_build_kdialog_file,
+        Commands::ChooseFile {
+            prompt: None,
+            of_type: vec!["public.png".into()],
+            default_location: Some("/tmp".into()),
+            invisibles: false,
+            multiple_selections_allowed: true,
+            showing_package_contents: false,
+        },
+        Some(Tool::Kdialog),
+        vec![
+            "--multiple",
+            "--separate-output",
+            "--getopenfilename",
+            "/tmp"
+        ]
+    );
+
+    #[test]
+    fn test_build_schema() {
+        assert!(build(&Commands::Schema, Some(Tool::Zenity)).is_err());
+    }
+
+    macro_rules! test_record {
+        ($name:ident, $command:expr, $tool:expr, $code:expr, $stdout:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = build(&$command, $tool).unwrap().record($code, $stdout, "");
+                let got = got.map_err(|x| x.downcast::<ProcessError>().unwrap().error.category);
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_record!(
+        test_record_ok,
+        dialog(&[], Some("")),
+        Some(Tool::Zenity),
+        Some(0),
+        "a\n",
+        Ok(json!({"button returned": "OK", "text returned": "a", "gave up": false}))
+    );
+    test_record!(
+        test_record_cancel,
+        dialog(&[], None),
+        Some(Tool::Zenity),
+        Some(1),
+        "",
+        Err(Category::UserCancelled)
+    );
+    test_record!(
+        test_record_extra_button,
+        dialog(&["A", "B", "C"], None),
+        Some(Tool::Zenity),
+        Some(1),
+        "A\n",
+        Ok(json!({"button returned": "A", "gave up": false}))
+    );
+    test_record!(
+        test_record_no_cancel_button,
+        dialog(&["A", "B"], None),
+        Some(Tool::Zenity),
+        Some(1),
+        "",
+        Err(Category::UserCancelled)
+    );
+    test_record!(
+        test_record_no_cancel_button_pressed,
+        dialog(&["A", "B"], None),
+        Some(Tool::Zenity),
+        Some(1),
+        "A\n",
+        Ok(json!({"button returned": "A", "gave up": false}))
+    );
+    test_record!(
+        test_record_gave_up,
+        dialog(&[], None),
+        Some(Tool::Zenity),
+        Some(5),
+        "",
+        Ok(json!({"button returned": "", "gave up": true}))
+    );
+    test_record!(
+        test_record_kdialog_third_button,
+        dialog(&["A", "B", "C"], None),
+        Some(Tool::Kdialog),
+        Some(2),
+        "",
+        Ok(json!({"button returned": "A"}))
+    );
+    test_record!(
+        test_record_failed,
+        dialog(&[], None),
+        Some(Tool::Kdialog),
+        Some(255),
+        "",
+        Err(Category::Unknown)
+    );
+    test_record!(
+        test_record_list,
+        choose_from_list(&[], true),
+        Some(Tool::Zenity),
+        Some(0),
+        "a\nb\n",
+        Ok(json!(["a", "b"]))
+    );
+    test_record!(
+        test_record_list_cancel,
+        choose_from_list(&[], false),
+        Some(Tool::Kdialog),
+        Some(1),
+        "",
+        Ok(json!(false))
+    );
+    test_record!(
+        test_record_folders,
+        choose_folder(None, true),
+        Some(Tool::Zenity),
+        Some(0),
+        "/tmp\n/home/a/\n",
+        Ok(json!(["/tmp/", "/home/a/"]))
+    );
+    test_record!(
+        test_record_folders_cancel,
+        choose_folder(None, false),
+        Some(Tool::Zenity),
+        Some(1),
+        "",
+        Err(Category::UserCancelled)
+    );
+    test_record!(
+        test_record_color,
+        Commands::ChooseColor { default: None },
+        Some(Tool::Zenity),
+        Some(0),
+        "rgb(255,128,0)\n",
+        Ok(json!([65535, 32896, 0]))
+    );
+}
@@ -0,0 +1,94 @@
+use crate::backend::stderr::{describe, unanswered};
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::response::Data;
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Appends commands to a file as json lines, e.g.
+/// `{"time":1700000000,"kind":"notification","summary":"Build","body":"passed"}`
+///
+/// Alerts and dialogs give up without returning any button, and choose from list is cancelled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFile {
+    path: PathBuf,
+}
+
+impl LogFile {
+    pub fn new<P: AsRef<Path>>(path: P) -> LogFile {
+        LogFile {
+            path: path.as_ref().into(),
+        }
+    }
+}
+
+impl Backend for LogFile {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let (summary, body) = describe(command)?;
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let line = json!({
+            "time": time,
+            "kind": command.kind(),
+            "summary": summary,
+            "body": body,
+        });
+        let mut f = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("open log file {:?}", self.path))?;
+        writeln!(f, "{line}")?;
+        let record = unanswered(command);
+        command.data(record.to_string(), record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value as Json;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn test_run() {
+        let path = env::temp_dir().join(format!("scptdisplay-log-{}", process::id()));
+        let _ = fs::remove_file(&path);
+        let log = LogFile::new(&path);
+        let alert = Commands::Alert {
+            text: "t".into(),
+            message: Some("m".into()),
+            alert_type: None,
+            buttons: vec![],
+            default_button: None,
+            cancel_button: None,
+            giving_up_after: None,
+        };
+        match log.run(&alert).unwrap() {
+            Data::Alert {
+                button, gave_up, ..
+            } => {
+                assert_eq!(Some("".into()), button);
+                assert!(gave_up);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+        log.run(&alert).unwrap();
+
+        let got = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let got: Vec<Json> = got
+            .lines()
+            .map(|x| serde_json::from_str(x).unwrap())
+            .collect();
+        assert_eq!(2, got.len());
+        assert_eq!("alert", got[0]["kind"]);
+        assert_eq!("t", got[0]["summary"]);
+        assert_eq!("m", got[0]["body"]);
+    }
+}
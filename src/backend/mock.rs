@@ -0,0 +1,295 @@
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::color::Color;
+use crate::error::{ProcessError, ScriptError};
+use crate::response::Data;
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as Json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A step of the mock script.
+///
+/// The next step of the command is consumed by each run, e.g.
+/// `{"command": "dialog", "button": "Deploy", "text": "foo"}`
+/// means the next dialog returns the button Deploy with the text foo.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Step {
+    /// notification, alert, dialog, choose_from_list, choose_file, choose_folder, choose_file_name, choose_color or say.
+    pub command: String,
+    pub button: Option<String>,
+    pub text: Option<String>,
+    /// How the user interacted with notification, e.g. actionClicked.
+    pub activation_type: Option<String>,
+    /// The action invoked for notification.
+    pub action: Option<String>,
+    /// The text replied to notification.
+    pub reply: Option<String>,
+    #[serde(default)]
+    pub gave_up: bool,
+    /// The items chosen from the list.
+    pub items: Option<Vec<String>>,
+    /// choose_from_list is cancelled.
+    #[serde(default)]
+    pub cancelled: bool,
+    /// The POSIX paths chosen by choose_file or choose_folder, or the path of choose_file_name.
+    pub paths: Option<Vec<String>>,
+    /// The color chosen by choose_color as #RRGGBB.
+    pub color: Option<String>,
+    /// Fail with the error instead of returning the result.
+    pub error: Option<StepError>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct StepError {
+    pub message: String,
+    pub number: Option<i32>,
+}
+
+impl Step {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        if let Some(x) = &self.error {
+            return Err(ProcessError {
+                code: Some(1),
+                error: ScriptError::new(&x.message, x.number),
+            }
+            .into());
+        }
+        if let Commands::ChooseFromList { .. } = command {
+            let record = if self.cancelled {
+                Json::Bool(false)
+            } else {
+                json!(self.items.clone().unwrap_or_default())
+            };
+            return command.data(record.to_string(), record);
+        }
+        if let Commands::ChooseFile { .. }
+        | Commands::ChooseFolder { .. }
+        | Commands::ChooseFileName { .. } = command
+        {
+            let record = json!(self.paths.clone().unwrap_or_default());
+            return command.data(record.to_string(), record);
+        }
+        if let Commands::ChooseColor { .. } = command {
+            let color: Color = self.color.as_deref().unwrap_or("#000000").parse()?;
+            let record = json!(color.0);
+            return command.data(record.to_string(), record);
+        }
+        let mut record = json!({});
+        if let Some(x) = &self.button {
+            record["button returned"] = x.as_str().into();
+        }
+        if let Some(x) = &self.text {
+            record["text returned"] = x.as_str().into();
+        }
+        if let Some(x) = &self.activation_type {
+            record["activation type"] = x.as_str().into();
+        }
+        if let Some(x) = &self.action {
+            record["action"] = x.as_str().into();
+        }
+        if let Some(x) = &self.reply {
+            record["reply"] = x.as_str().into();
+        }
+        if self.gave_up {
+            record["gave up"] = true.into();
+        }
+        command.data(record.to_string(), record)
+    }
+}
+
+/// Returns the results written in the mock script, a json list of [Step].
+///
+/// The consumed steps are saved in the state file, `FILE.state` next to the script.
+/// Remove the state file to replay the script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mock {
+    script: PathBuf,
+}
+
+impl Mock {
+    pub fn new<P: AsRef<Path>>(script: P) -> Mock {
+        Mock {
+            script: script.as_ref().into(),
+        }
+    }
+
+    fn state(&self) -> PathBuf {
+        let mut x = self.script.clone().into_os_string();
+        x.push(".state");
+        x.into()
+    }
+
+    /// Indices of the consumed steps.
+    fn consumed(&self) -> Result<Vec<usize>> {
+        match fs::read_to_string(self.state()) {
+            Ok(x) => Ok(serde_json::from_str(&x)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(vec![]),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl Backend for Mock {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let script = fs::read_to_string(&self.script)
+            .with_context(|| format!("read mock script {:?}", self.script))?;
+        let steps: Vec<Step> = serde_json::from_str(&script)
+            .with_context(|| format!("parse mock script {:?}", self.script))?;
+        let mut consumed = self.consumed()?;
+        let kind = command.kind();
+        let Some(i) = (0..steps.len()).find(|i| !consumed.contains(i) && steps[*i].command == kind)
+        else {
+            return Err(anyhow!("mock script has no more steps for {kind}"));
+        };
+        debug!("mock step {i}: {:?}", steps[i]);
+        consumed.push(i);
+        fs::write(self.state(), Json::from(consumed).to_string())?;
+        steps[i].run(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Category;
+    use std::env;
+    use std::process;
+
+    fn command(kind: &str) -> Commands {
+        let text = "t".to_string();
+        match kind {
+            "notification" => Commands::Notification {
+                text,
+                title: None,
+                subtitle: None,
+                sound_name: None,
+                urgency: None,
+                expire_timeout: None,
+                actions: vec![],
+                close_label: None,
+                reply: None,
+                timeout: None,
+                group: None,
+                open: None,
+                execute: None,
+                activate: None,
+                app_icon: None,
+                remove: None,
+            },
+            "choose_from_list" => Commands::ChooseFromList {
+                items: vec!["a".into(), "b".into()],
+                prompt: None,
+                title: None,
+                default_items: vec![],
+                ok_button_name: None,
+                cancel_button_name: None,
+                multiple_selections_allowed: true,
+                empty_selection_allowed: false,
+            },
+            "choose_color" => Commands::ChooseColor { default: None },
+            "choose_file" => Commands::ChooseFile {
+                prompt: None,
+                of_type: vec![],
+                default_location: None,
+                invisibles: false,
+                multiple_selections_allowed: false,
+                showing_package_contents: false,
+            },
+            _ => Commands::Dialog {
+                text,
+                default_answer: None,
+                hidden_answer: false,
+                buttons: vec![],
+                default_button: None,
+                cancel_button: None,
+                title: None,
+                icon: None,
+                giving_up_after: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_mock() {
+        let dir = env::temp_dir().join(format!("scptdisplay-mock-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("script.json");
+        fs::write(
+            &script,
+            r##"[
+  {"command": "dialog", "button": "Deploy", "text": "foo"},
+  {"command": "notification", "activation_type": "actionClicked", "action": "Open"},
+  {"command": "dialog", "error": {"message": "User canceled.", "number": -128}},
+  {"command": "choose_from_list", "items": ["b"]},
+  {"command": "choose_from_list", "cancelled": true},
+  {"command": "choose_file", "paths": ["/tmp/a.txt"]},
+  {"command": "choose_color", "color": "#FF8000"}
+]"##,
+        )
+        .unwrap();
+        let mock = Mock::new(&script);
+
+        match mock.run(&command("dialog")).unwrap() {
+            Data::Dialog {
+                text,
+                button,
+                gave_up,
+                ..
+            } => {
+                assert_eq!(Some("foo".to_string()), text);
+                assert_eq!(Some("Deploy".to_string()), button);
+                assert!(!gave_up);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+        let err = mock.run(&command("dialog")).unwrap_err();
+        let err = err.downcast_ref::<ProcessError>().unwrap();
+        assert_eq!(Category::UserCancelled, err.error.category);
+        assert!(mock.run(&command("dialog")).is_err());
+        assert_eq!(
+            Data::Notification {
+                activation_type: Some("actionClicked".into()),
+                action: Some("Open".into()),
+                reply: None,
+            },
+            mock.run(&command("notification")).unwrap()
+        );
+        assert_eq!(
+            Data::ChooseFromList {
+                items: vec!["b".into()],
+                cancelled: false,
+            },
+            mock.run(&command("choose_from_list")).unwrap()
+        );
+        assert_eq!(
+            Data::ChooseFromList {
+                items: vec![],
+                cancelled: true,
+            },
+            mock.run(&command("choose_from_list")).unwrap()
+        );
+        assert_eq!(
+            Data::ChooseFile {
+                paths: vec!["/tmp/a.txt".into()]
+            },
+            mock.run(&command("choose_file")).unwrap()
+        );
+        assert_eq!(
+            Data::Color {
+                components: [65535, 32896, 0],
+                hex: "#ff8000".into(),
+                rgb: [255, 128, 0],
+            },
+            mock.run(&command("choose_color")).unwrap()
+        );
+
+        // replay
+        fs::remove_file(mock.state()).unwrap();
+        assert!(mock.run(&command("dialog")).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,45 @@
+use crate::cli::Commands;
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use std::env;
+use std::path::PathBuf;
+
+pub mod alerter;
+pub mod dbus;
+pub mod linux;
+pub mod logfile;
+pub mod mock;
+pub mod osascript;
+pub mod osc;
+pub mod route;
+pub mod stderr;
+pub mod terminal_notifier;
+pub mod tui;
+
+/// Runs commands.
+pub trait Backend {
+    fn run(&self, command: &Commands) -> Result<Data>;
+    /// If true, the backend removes notifications by `notification remove`.
+    fn removes_notifications(&self) -> bool {
+        false
+    }
+}
+
+/// Find the program in PATH.
+pub(crate) fn find_program(name: &str) -> Option<PathBuf> {
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|x| x.join(name))
+        .find(|x| x.is_file())
+}
+
+/// Fail if the command removes notifications but the backend cannot, only terminal-notifier and alerter can remove them.
+pub(crate) fn deny_removal(command: &Commands, name: &str, backend: &dyn Backend) -> Result<()> {
+    match command {
+        Commands::Notification {
+            remove: Some(_), ..
+        } if !backend.removes_notifications() => {
+            Err(anyhow!("{name} backend cannot remove notifications"))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,209 @@
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::cmd::{Args, Cmd};
+use crate::error::{ProcessError, ScriptError};
+use crate::jxa;
+use crate::parse::Output;
+use crate::response::Data;
+use anyhow::{Error, Result};
+use clap::ValueEnum;
+use log::debug;
+use std::env;
+use std::process;
+
+/// Language of the script run by osascript.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Language {
+    #[default]
+    #[value(name = "applescript")]
+    AppleScript,
+    #[value(name = "javascript")]
+    JavaScript,
+}
+
+/// Runs commands by osascript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Osascript {
+    program: String,
+    language: Language,
+    inline: bool,
+}
+
+impl Default for Osascript {
+    fn default() -> Self {
+        Osascript {
+            program: "osascript".into(),
+            language: Language::default(),
+            inline: false,
+        }
+    }
+}
+
+impl Osascript {
+    /// osascript command.
+    pub fn program<S: Into<String>>(mut self, x: S) -> Self {
+        self.program = x.into();
+        self
+    }
+    pub fn language(mut self, x: Language) -> Self {
+        self.language = x;
+        self
+    }
+    /// Embed values into the script as literals instead of passing them to osascript as arguments.
+    /// Only for applescript.
+    pub fn inline(mut self, x: bool) -> Self {
+        self.inline = x;
+        self
+    }
+
+    pub fn cmd(&self, command: &Commands) -> Result<Cmd> {
+        let mut c = Cmd::new(&self.program);
+        if self.language == Language::JavaScript {
+            c.pair("-l", Some("JavaScript"));
+            c.pair("-e", Some(jxa::PROGRAM));
+            c.arg(Some(String::from(command.jxa()?)));
+            return Ok(c);
+        }
+        // print values in source form to parse them
+        c.pair("-s", Some("s"));
+        let mut args = Args::new(self.inline);
+        let a = command.script(&mut args)?;
+        let script = args.script(a);
+        c.pair("-e", Some(format!("{script}")));
+        for x in args.values() {
+            c.arg(Some(x));
+        }
+        Ok(c)
+    }
+
+    pub fn parse_stdout(&self, command: &Commands, output: Vec<u8>) -> Result<Data> {
+        let raw = String::from_utf8(output)?;
+        let record = match (command, self.language) {
+            (Commands::Notification { .. } | Commands::Say { .. } | Commands::Schema, _) => {
+                serde_json::Value::Null
+            }
+            (
+                Commands::ChooseFromList { .. }
+                | Commands::ChooseFile { .. }
+                | Commands::ChooseFolder { .. }
+                | Commands::ChooseFileName { .. }
+                | Commands::ChooseColor { .. },
+                Language::AppleScript,
+            ) => Output::list(&raw)?,
+            (_, Language::AppleScript) => Output::record(&raw)?,
+            (_, Language::JavaScript) => jxa::parse(&raw)?,
+        };
+        command.data(raw, record)
+    }
+}
+
+impl Backend for Osascript {
+    /// Run the command.
+    ///
+    /// If osascript fails, the error is [ProcessError].
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let c = self.cmd(command)?;
+        debug!("cmd: {c:?}");
+
+        let x = process::Command::from(c)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .env_clear()
+            .envs(env::vars())
+            .spawn()?
+            .wait_with_output()?;
+        if x.status.success() {
+            return self.parse_stdout(command, x.stdout);
+        }
+        let code = x.status.code();
+        match String::from_utf8(x.stderr) {
+            Ok(x) => Err(ProcessError {
+                code,
+                error: ScriptError::parse(&x),
+            }
+            .into()),
+            Err(x) => Err(ProcessError {
+                code,
+                error: ScriptError::unknown(format!("{}", Error::new(x))),
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Category;
+
+    fn notification() -> Commands {
+        Commands::Notification {
+            text: "x".into(),
+            title: None,
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: None,
+        }
+    }
+
+    #[test]
+    fn test_run_failed() {
+        let got = Osascript::default()
+            .program("false")
+            .run(&notification())
+            .unwrap_err();
+        let got = got.downcast_ref::<ProcessError>().unwrap();
+        assert_eq!(Some(1), got.code);
+        assert_eq!(Category::Unknown, got.error.category);
+    }
+
+    #[test]
+    fn test_run_notification() {
+        let got = Osascript::default()
+            .program("true")
+            .run(&notification())
+            .unwrap();
+        assert_eq!(
+            Data::Notification {
+                activation_type: None,
+                action: None,
+                reply: None
+            },
+            got
+        );
+    }
+
+    #[test]
+    fn test_run_without_script() {
+        let osascript = Osascript::default().program("true");
+        assert!(osascript.run(&Commands::Schema).is_err());
+        assert!(osascript.run(&Commands::Voices).is_err());
+        let osascript = osascript.language(Language::JavaScript);
+        assert!(osascript.run(&Commands::Schema).is_err());
+        assert!(osascript.run(&Commands::Voices).is_err());
+    }
+
+    #[test]
+    fn test_run_pick() {
+        let pick = Commands::Pick {
+            null: false,
+            multi: false,
+            preselect: None,
+            prompt: None,
+            title: None,
+        };
+        assert!(Osascript::default().program("true").run(&pick).is_err());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::backend::linux::summary;
+use crate::backend::tui::TTY;
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use log::debug;
+use serde_json::Value as Json;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+/// An escape sequence that posts a desktop notification.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Flavor {
+    /// OSC 9, iTerm2, WezTerm, Ghostty and Windows Terminal.
+    Osc9,
+    /// OSC 777, foot, urxvt, WezTerm and Ghostty.
+    Osc777,
+    /// OSC 99, kitty.
+    Osc99,
+}
+
+impl Flavor {
+    /// Detect the flavor by TERM_PROGRAM, LC_TERMINAL and TERM, OSC 9 if unknown.
+    fn detect<F: Fn(&str) -> Option<String>>(var: F) -> Flavor {
+        let program = var("TERM_PROGRAM").or_else(|| var("LC_TERMINAL"));
+        let term = var("TERM").unwrap_or_default();
+        let osc9 = matches!(
+            program.as_deref(),
+            Some("iTerm.app" | "iTerm2" | "WezTerm" | "ghostty")
+        );
+        if osc9 {
+            Flavor::Osc9
+        } else if term == "xterm-kitty" || var("KITTY_WINDOW_ID").is_some() {
+            Flavor::Osc99
+        } else if term.starts_with("foot") || term.starts_with("rxvt") {
+            Flavor::Osc777
+        } else {
+            Flavor::Osc9
+        }
+    }
+}
+
+/// A terminal multiplexer that needs the sequence to be wrapped to reach the outer terminal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Multiplexer {
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    fn detect<F: Fn(&str) -> Option<String>>(var: F) -> Option<Multiplexer> {
+        if var("TMUX").is_some() {
+            Some(Multiplexer::Tmux)
+        } else if var("STY").is_some() {
+            Some(Multiplexer::Screen)
+        } else {
+            None
+        }
+    }
+
+    /// Wrap the sequence in a DCS passthrough.
+    ///
+    /// tmux requires `set -g allow-passthrough on`.
+    /// screen ends the DCS at the first ST, so the sequences inside end with BEL instead.
+    fn wrap(&self, seq: &str) -> String {
+        match self {
+            Multiplexer::Tmux => format!("\x1bPtmux;{}\x1b\\", seq.replace('\x1b', "\x1b\x1b")),
+            Multiplexer::Screen => format!("\x1bP{}\x1b\\", seq.replace("\x1b\\", "\x07")),
+        }
+    }
+}
+
+/// Replace control characters that would terminate the sequence.
+fn sanitize(x: &str) -> String {
+    x.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+/// The escape sequence of the notification.
+fn sequence(flavor: Flavor, summary: &str, body: Option<&str>) -> String {
+    let summary = sanitize(summary);
+    let body = body.map(sanitize);
+    match flavor {
+        Flavor::Osc9 => match body {
+            Some(b) => format!("\x1b]9;{summary}: {b}\x07"),
+            None => format!("\x1b]9;{summary}\x07"),
+        },
+        // the title ends at the first semicolon
+        Flavor::Osc777 => format!(
+            "\x1b]777;notify;{};{}\x07",
+            summary.replace(';', ","),
+            body.unwrap_or_default()
+        ),
+        Flavor::Osc99 => match body {
+            Some(b) => format!("\x1b]99;i=1:d=0;{summary}\x1b\\\x1b]99;i=1:d=1:p=body;{b}\x1b\\"),
+            None => format!("\x1b]99;;{summary}\x1b\\"),
+        },
+    }
+}
+
+/// Posts notifications by terminal escape sequences written to the controlling terminal,
+/// so that notifications from remote shells reach the local desktop.
+///
+/// The sequence is passed through tmux and screen.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Osc {
+    flavor: Option<Flavor>,
+}
+
+impl Osc {
+    /// The escape sequence.
+    /// Default: detected by the environment variables.
+    pub fn flavor(mut self, x: Option<Flavor>) -> Self {
+        self.flavor = x;
+        self
+    }
+
+    fn build(&self, command: &Commands) -> Result<String> {
+        let Commands::Notification {
+            text,
+            title,
+            subtitle,
+            ..
+        } = command
+        else {
+            return Err(anyhow!("osc backend supports only notification"));
+        };
+        let var = |x: &str| env::var(x).ok();
+        let flavor = self.flavor.unwrap_or_else(|| Flavor::detect(var));
+        let (summary, body) = summary(text, title.as_deref(), subtitle.as_deref());
+        let seq = sequence(flavor, &summary, body.as_deref());
+        Ok(match Multiplexer::detect(var) {
+            Some(x) => x.wrap(&seq),
+            None => seq,
+        })
+    }
+}
+
+impl Backend for Osc {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let seq = self.build(command)?;
+        debug!("osc: {seq:?}");
+        let mut tty = OpenOptions::new()
+            .write(true)
+            .open(TTY)
+            .map_err(|err| anyhow!("no controlling terminal: {err}"))?;
+        tty.write_all(seq.as_bytes())?;
+        tty.flush()?;
+        command.data(String::new(), Json::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(xs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |k| xs.iter().find(|(x, _)| *x == k).map(|(_, v)| v.to_string())
+    }
+
+    macro_rules! test_detect {
+        ($name:ident, $env:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!($want, Flavor::detect(env(&$env)));
+            }
+        };
+    }
+
+    test_detect!(
+        test_detect_iterm2,
+        [("TERM_PROGRAM", "iTerm.app"), ("TERM", "xterm-256color")],
+        Flavor::Osc9
+    );
+    test_detect!(
+        test_detect_iterm2_over_ssh,
+        [("LC_TERMINAL", "iTerm2")],
+        Flavor::Osc9
+    );
+    test_detect!(test_detect_kitty, [("TERM", "xterm-kitty")], Flavor::Osc99);
+    test_detect!(test_detect_foot, [("TERM", "foot")], Flavor::Osc777);
+    test_detect!(test_detect_unknown, [], Flavor::Osc9);
+
+    macro_rules! test_sequence {
+        ($name:ident, $flavor:expr, $summary:expr, $body:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!($want, sequence($flavor, $summary, $body));
+            }
+        };
+    }
+
+    test_sequence!(
+        test_sequence_osc9,
+        Flavor::Osc9,
+        "x",
+        Some("a\x07b"),
+        "\x1b]9;x: a b\x07"
+    );
+    test_sequence!(
+        test_sequence_osc777,
+        Flavor::Osc777,
+        "x;y",
+        Some("a;b"),
+        "\x1b]777;notify;x,y;a;b\x07"
+    );
+    test_sequence!(
+        test_sequence_osc99,
+        Flavor::Osc99,
+        "x",
+        Some("a"),
+        "\x1b]99;i=1:d=0;x\x1b\\\x1b]99;i=1:d=1:p=body;a\x1b\\"
+    );
+    test_sequence!(
+        test_sequence_osc99_summary,
+        Flavor::Osc99,
+        "x",
+        None,
+        "\x1b]99;;x\x1b\\"
+    );
+
+    #[test]
+    fn test_wrap_tmux() {
+        assert_eq!(
+            "\x1bPtmux;\x1b\x1b]9;x\x07\x1b\\",
+            Multiplexer::Tmux.wrap("\x1b]9;x\x07")
+        );
+    }
+
+    #[test]
+    fn test_wrap_screen() {
+        assert_eq!(
+            "\x1bP\x1b]9;x\x07\x1b\\",
+            Multiplexer::Screen.wrap("\x1b]9;x\x07")
+        );
+        assert_eq!(
+            "\x1bP\x1b]99;i=1:d=0;x\x07\x1b]99;i=1:d=1:p=body;y\x07\x1b\\",
+            Multiplexer::Screen.wrap(&sequence(Flavor::Osc99, "x", Some("y")))
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use crate::backend::{deny_removal, Backend};
+use crate::cli::Commands;
+use crate::error::Category;
+use crate::response::{script_error, Attempt, Data};
+use anyhow::{anyhow, Result};
+use log::debug;
+use std::thread;
+
+/// How the command is routed to the backends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// Try each backend in order until one succeeds or the user cancels.
+    Chain,
+    /// Run the command by all the backends at once, the result is the first success in order.
+    FanOut,
+}
+
+/// Runs the command by multiple backends.
+pub struct Route {
+    mode: Mode,
+    backends: Vec<(String, Box<dyn Backend + Send + Sync>)>,
+}
+
+impl Route {
+    pub fn new(mode: Mode) -> Route {
+        Route {
+            mode,
+            backends: vec![],
+        }
+    }
+
+    /// Add the backend with its name.
+    pub fn push<S: Into<String>>(
+        mut self,
+        name: S,
+        backend: Box<dyn Backend + Send + Sync>,
+    ) -> Self {
+        self.backends.push((name.into(), backend));
+        self
+    }
+
+    /// Run the command, return the result and the outcomes of the backends.
+    ///
+    /// Removing notifications fails by the backends that cannot remove them.
+    /// The result is the last error if no backend succeeds.
+    pub fn dispatch(&self, command: &Commands) -> (Result<Data>, Vec<Attempt>) {
+        let results = match self.mode {
+            Mode::Chain => self.chain(command),
+            Mode::FanOut => self.fan_out(command),
+        };
+        let attempts = results.iter().map(|(k, v)| Attempt::new(*k, v)).collect();
+        let mut err = None;
+        for (_, result) in results {
+            match result {
+                Ok(x) => return (Ok(x), attempts),
+                Err(x) => err = Some(x),
+            }
+        }
+        (Err(err.unwrap_or_else(|| anyhow!("no backends"))), attempts)
+    }
+
+    /// Run the backends one after another until one succeeds or the user cancels.
+    fn chain(&self, command: &Commands) -> Vec<(&str, Result<Data>)> {
+        let mut xs = vec![];
+        for (name, backend) in &self.backends {
+            let result = run(command, name, backend.as_ref());
+            // the user answered, do not ask again
+            let done = match &result {
+                Ok(_) => true,
+                Err(x) => script_error(x).category == Category::UserCancelled,
+            };
+            xs.push((name.as_str(), result));
+            if done {
+                break;
+            }
+        }
+        xs
+    }
+
+    /// Run all the backends in threads, so that a backend waiting for the user does not delay the others.
+    fn fan_out(&self, command: &Commands) -> Vec<(&str, Result<Data>)> {
+        thread::scope(|s| {
+            let handles: Vec<_> = self
+                .backends
+                .iter()
+                .map(|(name, backend)| {
+                    (
+                        name.as_str(),
+                        s.spawn(move || run(command, name, backend.as_ref())),
+                    )
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(name, x)| {
+                    let result = x
+                        .join()
+                        .unwrap_or_else(|_| Err(anyhow!("{name} backend panicked")));
+                    (name, result)
+                })
+                .collect()
+        })
+    }
+}
+
+/// Run the command by the backend.
+fn run(command: &Commands, name: &str, backend: &dyn Backend) -> Result<Data> {
+    let result = deny_removal(command, name, backend).and_then(|_| backend.run(command));
+    debug!("backend {name}: {result:?}");
+    result
+}
+
+impl Backend for Route {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        self.dispatch(command).0
+    }
+    fn removes_notifications(&self) -> bool {
+        self.backends.iter().any(|(_, x)| x.removes_notifications())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Remove;
+    use crate::error::ScriptError;
+    use crate::response::Code;
+    use std::sync::{mpsc, Mutex};
+    use std::time::Duration;
+
+    /// Fails with the error number or returns the notification.
+    struct Stub(Option<i32>);
+
+    impl Backend for Stub {
+        fn run(&self, command: &Commands) -> Result<Data> {
+            match self.0 {
+                Some(n) => Err(ScriptError::new("failed", Some(n)).into()),
+                None => command.data(String::new(), serde_json::Value::Null),
+            }
+        }
+    }
+
+    /// Waits for a message from another backend.
+    struct Wait(Mutex<mpsc::Receiver<()>>);
+
+    impl Backend for Wait {
+        fn run(&self, command: &Commands) -> Result<Data> {
+            self.0
+                .lock()
+                .unwrap()
+                .recv_timeout(Duration::from_secs(5))?;
+            command.data(String::new(), serde_json::Value::Null)
+        }
+    }
+
+    /// Sends a message to another backend.
+    struct Signal(mpsc::Sender<()>);
+
+    impl Backend for Signal {
+        fn run(&self, command: &Commands) -> Result<Data> {
+            self.0.send(())?;
+            command.data(String::new(), serde_json::Value::Null)
+        }
+    }
+
+    fn notification() -> Commands {
+        removal(None)
+    }
+
+    fn removal(remove: Option<&str>) -> Commands {
+        Commands::Notification {
+            text: "t".into(),
+            title: None,
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            remove: remove.map(|group| Remove::Remove {
+                group: group.into(),
+            }),
+        }
+    }
+
+    macro_rules! test_dispatch {
+        ($name:ident, $mode:expr, $stubs:expr, $ok:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let route = $stubs
+                    .into_iter()
+                    .enumerate()
+                    .fold(Route::new($mode), |r, (i, x)| {
+                        r.push(format!("b{i}"), Box::new(Stub(x)))
+                    });
+                let (result, attempts) = route.dispatch(&notification());
+                assert_eq!($ok, result.is_ok());
+                let got: Vec<(String, Code)> = attempts
+                    .into_iter()
+                    .map(|x| (x.backend, x.result))
+                    .collect();
+                let want: Vec<(String, Code)> =
+                    $want.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+                assert_eq!(want, got);
+            }
+        };
+    }
+
+    test_dispatch!(
+        test_dispatch_chain_fallback,
+        Mode::Chain,
+        [Some(-1743), None, None],
+        true,
+        [("b0", Code::Err), ("b1", Code::Ok)]
+    );
+    test_dispatch!(
+        test_dispatch_chain_cancelled,
+        Mode::Chain,
+        [Some(-128), None],
+        false,
+        [("b0", Code::Err)]
+    );
+    test_dispatch!(
+        test_dispatch_chain_failed,
+        Mode::Chain,
+        [Some(1), Some(2)],
+        false,
+        [("b0", Code::Err), ("b1", Code::Err)]
+    );
+    test_dispatch!(
+        test_dispatch_fan_out,
+        Mode::FanOut,
+        [None, Some(1), None],
+        true,
+        [("b0", Code::Ok), ("b1", Code::Err), ("b2", Code::Ok)]
+    );
+
+    #[test]
+    fn test_dispatch_fan_out_at_once() {
+        // the first backend waits for the second one, which never runs if they run in order
+        let (tx, rx) = mpsc::channel();
+        let route = Route::new(Mode::FanOut)
+            .push("b0", Box::new(Wait(Mutex::new(rx))))
+            .push("b1", Box::new(Signal(tx)));
+        let (result, attempts) = route.dispatch(&notification());
+        assert!(result.is_ok());
+        let got: Vec<Code> = attempts.into_iter().map(|x| x.result).collect();
+        assert_eq!(vec![Code::Ok, Code::Ok], got);
+    }
+
+    #[test]
+    fn test_dispatch_removal() {
+        let route = Route::new(Mode::Chain).push("b0", Box::new(Stub(None)));
+        let (result, attempts) = route.dispatch(&removal(Some("ci")));
+        assert!(result.is_err());
+        assert_eq!(Code::Err, attempts[0].result);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::backend::linux::summary;
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value as Json};
+use std::io::{self, Write};
+
+/// The summary and the body of the command to print.
+pub(crate) fn describe(command: &Commands) -> Result<(String, Option<String>)> {
+    Ok(match command {
+        Commands::Notification {
+            text,
+            title,
+            subtitle,
+            ..
+        } => summary(text, title.as_deref(), subtitle.as_deref()),
+        Commands::Alert { text, message, .. } => (text.clone(), message.clone()),
+        Commands::Dialog { text, title, .. } => match title {
+            Some(t) => (t.clone(), Some(text.clone())),
+            None => (text.clone(), None),
+        },
+        Commands::ChooseFromList { items, prompt, .. } => (
+            prompt
+                .as_deref()
+                .unwrap_or("Please make your selection:")
+                .into(),
+            Some(items.join("\n")),
+        ),
+        Commands::Say { text, .. } => (text.clone(), None),
+        x => return Err(anyhow!("cannot print {}", x.kind())),
+    })
+}
+
+/// The record of the command that nobody can answer.
+///
+/// Alerts and dialogs give up without returning any button, and choose from list is cancelled.
+pub(crate) fn unanswered(command: &Commands) -> Json {
+    match command {
+        Commands::Notification { .. } | Commands::Say { .. } => Json::Null,
+        Commands::ChooseFromList { .. } => Json::Bool(false),
+        _ => json!({"button returned": "", "gave up": true}),
+    }
+}
+
+/// Prints commands to stderr, the last resort of a fallback chain.
+///
+/// Alerts and dialogs give up without returning any button, and choose from list is cancelled.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Stderr {}
+
+impl Backend for Stderr {
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let (summary, body) = describe(command)?;
+        let mut w = io::stderr().lock();
+        writeln!(w, "{summary}")?;
+        if let Some(x) = body {
+            writeln!(w, "{x}")?;
+        }
+        let record = unanswered(command);
+        command.data(record.to_string(), record)
+    }
+}
@@ -0,0 +1,189 @@
+use crate::backend::Backend;
+use crate::cli::{Commands, Remove};
+use crate::cmd::Cmd;
+use crate::error::{ProcessError, ScriptError};
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use log::debug;
+use serde_json::Value as Json;
+use std::process;
+
+/// Posts and removes notifications by terminal-notifier.
+///
+/// See https://github.com/julienXX/terminal-notifier
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalNotifier {
+    program: String,
+}
+
+impl Default for TerminalNotifier {
+    fn default() -> Self {
+        TerminalNotifier {
+            program: "terminal-notifier".into(),
+        }
+    }
+}
+
+impl TerminalNotifier {
+    /// terminal-notifier command.
+    pub fn program<S: Into<String>>(mut self, x: S) -> Self {
+        self.program = x.into();
+        self
+    }
+
+    pub fn cmd(&self, command: &Commands) -> Result<Cmd> {
+        let Commands::Notification {
+            text,
+            title,
+            subtitle,
+            sound_name,
+            group,
+            open,
+            execute,
+            activate,
+            app_icon,
+            remove,
+            ..
+        } = command
+        else {
+            return Err(anyhow!(
+                "terminal-notifier backend supports only notification"
+            ));
+        };
+        let mut c = Cmd::new(&self.program);
+        if let Some(Remove::Remove { group }) = remove {
+            c.pair("-remove", Some(group));
+            return Ok(c);
+        }
+        c.pair("-message", Some(text));
+        c.pair("-title", title.as_deref());
+        c.pair("-subtitle", subtitle.as_deref());
+        c.pair("-sound", sound_name.as_deref());
+        c.pair("-group", group.as_deref());
+        c.pair("-open", open.as_deref());
+        c.pair("-execute", execute.as_deref());
+        c.pair("-activate", activate.as_deref());
+        c.pair("-appIcon", app_icon.as_deref());
+        Ok(c)
+    }
+}
+
+impl Backend for TerminalNotifier {
+    /// Run the command.
+    ///
+    /// If terminal-notifier fails, the error is [ProcessError].
+    fn run(&self, command: &Commands) -> Result<Data> {
+        let c = self.cmd(command)?;
+        debug!("cmd: {c:?}");
+
+        let out = process::Command::from(c)
+            .stdin(process::Stdio::null())
+            .output()?;
+        let stdout = String::from_utf8(out.stdout)?;
+        if !out.status.success() {
+            let code = out.status.code();
+            let message = match String::from_utf8_lossy(&out.stderr).trim() {
+                "" => format!("exit status {code:?}"),
+                x => x.to_string(),
+            };
+            return Err(ProcessError {
+                code,
+                error: ScriptError::unknown(message),
+            }
+            .into());
+        }
+        command.data(stdout, Json::Null)
+    }
+    fn removes_notifications(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Category;
+
+    fn notification(remove: Option<&str>) -> Commands {
+        Commands::Notification {
+            text: "t".into(),
+            title: Some("x".into()),
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: Some("ci".into()),
+            open: Some("https://example.com".into()),
+            execute: None,
+            activate: Some("com.apple.Terminal".into()),
+            app_icon: None,
+            remove: remove.map(|group| Remove::Remove {
+                group: group.into(),
+            }),
+        }
+    }
+
+    fn args(x: Cmd) -> Vec<String> {
+        process::Command::from(x)
+            .get_args()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_cmd_notification() {
+        let got = TerminalNotifier::default()
+            .cmd(&notification(None))
+            .unwrap();
+        assert_eq!(
+            vec![
+                "-message",
+                "t",
+                "-title",
+                "x",
+                "-group",
+                "ci",
+                "-open",
+                "https://example.com",
+                "-activate",
+                "com.apple.Terminal",
+            ],
+            args(got)
+        );
+    }
+
+    #[test]
+    fn test_cmd_remove() {
+        let got = TerminalNotifier::default()
+            .cmd(&notification(Some("ci")))
+            .unwrap();
+        assert_eq!(vec!["-remove", "ci"], args(got));
+    }
+
+    #[test]
+    fn test_run() {
+        let got = TerminalNotifier::default()
+            .program("true")
+            .run(&notification(None))
+            .unwrap();
+        assert_eq!(
+            Data::Notification {
+                activation_type: None,
+                action: None,
+                reply: None
+            },
+            got
+        );
+        let got = TerminalNotifier::default()
+            .program("false")
+            .run(&notification(None))
+            .unwrap_err();
+        let got = got.downcast_ref::<ProcessError>().unwrap();
+        assert_eq!(Some(1), got.code);
+        assert_eq!(Category::Unknown, got.error.category);
+    }
+}
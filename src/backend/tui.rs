@@ -0,0 +1,461 @@
+use crate::backend::linux::{buttons, summary};
+use crate::backend::Backend;
+use crate::cli::Commands;
+use crate::error::ScriptError;
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, queue, style, terminal};
+use log::debug;
+use serde_json::{json, Value as Json};
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+pub(crate) const TTY: &str = "/dev/tty";
+
+/// True if there is no graphical session to display dialogs, e.g. an ssh session or a console.
+pub(crate) fn is_headless() -> bool {
+    if cfg!(target_os = "macos") {
+        env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some()
+    } else {
+        env::var_os("DISPLAY").is_none() && env::var_os("WAYLAND_DISPLAY").is_none()
+    }
+}
+
+/// True if the process has a controlling terminal.
+pub(crate) fn has_terminal() -> bool {
+    OpenOptions::new().read(true).write(true).open(TTY).is_ok()
+}
+
+/// How the form was closed.
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Pressed(String),
+    /// Interrupted by Ctrl-C.
+    Interrupted,
+    GaveUp,
+}
+
+/// An alert or a dialog rendered in the terminal.
+#[derive(Debug, Clone, PartialEq)]
+struct Form {
+    title: Option<String>,
+    text: String,
+    /// The text entered and whether it is hidden, present if there is an edit field.
+    answer: Option<(String, bool)>,
+    /// Buttons from left to right, at least one.
+    buttons: Vec<String>,
+    cancel: Option<String>,
+    /// Index of the selected button.
+    selected: usize,
+    giving_up_after: Option<u8>,
+}
+
+/// Index of the button by its name or number.
+fn position(buttons: &[String], x: &str) -> Option<usize> {
+    match x.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).filter(|i| *i < buttons.len()),
+        Err(_) => buttons.iter().position(|b| b == x),
+    }
+}
+
+impl Form {
+    fn new(command: &Commands) -> Result<Form> {
+        let (title, text, answer, buttons, default, cancel, giving_up_after) = match command {
+            Commands::Alert {
+                text,
+                message,
+                buttons: xs,
+                default_button,
+                cancel_button,
+                giving_up_after,
+                ..
+            } => {
+                let (buttons, cancel) = buttons(xs, cancel_button.as_deref(), &["OK"], None);
+                let text = match message {
+                    Some(m) => format!("{text}\n\n{m}"),
+                    None => text.clone(),
+                };
+                (
+                    None,
+                    text,
+                    None,
+                    buttons,
+                    default_button.as_deref(),
+                    cancel,
+                    *giving_up_after,
+                )
+            }
+            Commands::Dialog {
+                text,
+                default_answer,
+                hidden_answer,
+                buttons: xs,
+                default_button,
+                cancel_button,
+                title,
+                giving_up_after,
+                ..
+            } => {
+                let default = default_button.as_deref().or(xs.is_empty().then_some("OK"));
+                let (buttons, cancel) = buttons(
+                    xs,
+                    cancel_button.as_deref(),
+                    &["Cancel", "OK"],
+                    Some("Cancel"),
+                );
+                (
+                    title.clone(),
+                    text.clone(),
+                    default_answer.clone().map(|x| (x, *hidden_answer)),
+                    buttons,
+                    default,
+                    cancel,
+                    *giving_up_after,
+                )
+            }
+            x => return Err(anyhow!("tui backend does not support {}", x.kind())),
+        };
+        // the rightmost button is selected if there is no default button
+        let selected = default
+            .and_then(|x| position(&buttons, x))
+            .unwrap_or(buttons.len() - 1);
+        Ok(Form {
+            title,
+            text,
+            answer,
+            buttons,
+            cancel,
+            selected,
+            giving_up_after,
+        })
+    }
+
+    /// Handle the key, return the outcome if the form is closed.
+    fn key(&mut self, key: KeyEvent) -> Option<Outcome> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Some(Outcome::Interrupted),
+            KeyCode::Char('u') if ctrl => {
+                if let Some((x, _)) = &mut self.answer {
+                    x.clear();
+                }
+            }
+            KeyCode::Char(c) if !ctrl => {
+                if let Some((x, _)) = &mut self.answer {
+                    x.push(c);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some((x, _)) = &mut self.answer {
+                    x.pop();
+                }
+            }
+            KeyCode::Left | KeyCode::BackTab => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Right | KeyCode::Tab => {
+                self.selected = (self.selected + 1).min(self.buttons.len() - 1)
+            }
+            KeyCode::Enter => return Some(Outcome::Pressed(self.buttons[self.selected].clone())),
+            KeyCode::Esc => return self.cancel.clone().map(Outcome::Pressed),
+            _ => {}
+        }
+        None
+    }
+
+    /// Lines to render, `remaining` is the number of seconds until the form gives up.
+    fn lines(&self, remaining: Option<u64>) -> Vec<String> {
+        let mut xs = vec![];
+        if let Some(x) = &self.title {
+            xs.push(format!("== {x} =="));
+        }
+        xs.extend(self.text.lines().map(String::from));
+        if let Some((x, hidden)) = &self.answer {
+            let x = if *hidden {
+                "*".repeat(x.chars().count())
+            } else {
+                x.clone()
+            };
+            xs.push(format!("> {x}"));
+        }
+        let buttons: Vec<String> = self
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if i == self.selected {
+                    format!("<{b}>")
+                } else {
+                    format!("[{b}]")
+                }
+            })
+            .collect();
+        xs.push(buttons.join(" "));
+        if let Some(n) = remaining {
+            xs.push(format!("gives up in {n}s"));
+        }
+        xs
+    }
+
+    /// The record of display dialog.
+    fn record(&self, outcome: Outcome) -> Result<Json> {
+        let cancelled =
+            || -> anyhow::Error { ScriptError::new("User canceled.", Some(-128)).into() };
+        let mut record = json!({});
+        let gave_up = outcome == Outcome::GaveUp;
+        match outcome {
+            Outcome::Interrupted => return Err(cancelled()),
+            Outcome::Pressed(x) if self.cancel.as_ref() == Some(&x) => return Err(cancelled()),
+            Outcome::Pressed(x) => record["button returned"] = x.into(),
+            Outcome::GaveUp => record["button returned"] = "".into(),
+        }
+        if let Some((x, _)) = &self.answer {
+            record["text returned"] = x.as_str().into();
+        }
+        if self.giving_up_after.is_some() {
+            record["gave up"] = gave_up.into();
+        }
+        Ok(record)
+    }
+}
+
+/// Renders the lines in the terminal, the previous lines are overwritten.
+struct Screen {
+    tty: File,
+    /// The number of the lines rendered.
+    height: u16,
+}
+
+impl Screen {
+    fn open() -> Result<Screen> {
+        let tty = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(TTY)
+            .map_err(|err| anyhow!("no controlling terminal: {err}"))?;
+        terminal::enable_raw_mode()?;
+        Ok(Screen { tty, height: 0 })
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        queue!(self.tty, cursor::MoveToColumn(0))?;
+        if self.height > 0 {
+            queue!(self.tty, cursor::MoveUp(self.height))?;
+        }
+        queue!(
+            self.tty,
+            terminal::Clear(terminal::ClearType::FromCursorDown)
+        )?;
+        self.height = 0;
+        Ok(())
+    }
+
+    fn draw(&mut self, lines: &[String]) -> Result<()> {
+        self.clear()?;
+        for x in lines {
+            queue!(self.tty, style::Print(x), style::Print("\r\n"))?;
+        }
+        self.tty.flush()?;
+        self.height = lines.len().try_into().unwrap_or(u16::MAX);
+        Ok(())
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = self.clear();
+        let _ = self.tty.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Runs commands in the controlling terminal, for ssh sessions and consoles.
+///
+/// Alerts and dialogs are rendered as a form: arrow keys or Tab select the button,
+/// Return presses it, Escape presses the cancel button and Ctrl-C cancels.
+/// Notifications are printed to the terminal.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tui {}
+
+impl Tui {
+    fn notify(&self, text: &str, title: Option<&str>, subtitle: Option<&str>) -> Result<()> {
+        let (summary, body) = summary(text, title, subtitle);
+        let mut tty = OpenOptions::new()
+            .write(true)
+            .open(TTY)
+            .map_err(|err| anyhow!("no controlling terminal: {err}"))?;
+        writeln!(tty, "{summary}")?;
+        if let Some(x) = body {
+            writeln!(tty, "{x}")?;
+        }
+        Ok(())
+    }
+
+    fn show(&self, form: &mut Form) -> Result<Outcome> {
+        let deadline = form
+            .giving_up_after
+            .map(|x| Instant::now() + Duration::from_secs(x.into()));
+        let mut screen = Screen::open()?;
+        loop {
+            let remaining = deadline.map(|x| x.saturating_duration_since(Instant::now()));
+            if remaining == Some(Duration::ZERO) {
+                return Ok(Outcome::GaveUp);
+            }
+            screen.draw(&form.lines(remaining.map(|x| x.as_millis().div_ceil(1000) as u64)))?;
+            // redraw the countdown every second
+            let wait = remaining.map_or(Duration::MAX, |x| x.min(Duration::from_secs(1)));
+            if !event::poll(wait)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Release {
+                    if let Some(x) = form.key(key) {
+                        return Ok(x);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Backend for Tui {
+    /// Run the command.
+    ///
+    /// If the cancel button is pressed or interrupted, the error is [ScriptError].
+    fn run(&self, command: &Commands) -> Result<Data> {
+        if let Commands::Notification {
+            text,
+            title,
+            subtitle,
+            ..
+        } = command
+        {
+            self.notify(text, title.as_deref(), subtitle.as_deref())?;
+            return command.data(String::new(), Json::Null);
+        }
+        let mut form = Form::new(command)?;
+        let outcome = self.show(&mut form)?;
+        debug!("tui outcome: {outcome:?}");
+        let record = form.record(outcome)?;
+        command.data(record.to_string(), record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Category;
+
+    fn dialog(buttons: &[&str], default_answer: Option<&str>) -> Commands {
+        Commands::Dialog {
+            text: "t".into(),
+            default_answer: default_answer.map(String::from),
+            hidden_answer: true,
+            buttons: buttons.iter().map(|x| x.to_string()).collect(),
+            default_button: None,
+            cancel_button: None,
+            title: Some("x".into()),
+            icon: None,
+            giving_up_after: Some(3),
+        }
+    }
+
+    fn alert() -> Commands {
+        Commands::Alert {
+            text: "t".into(),
+            message: Some("m".into()),
+            alert_type: None,
+            buttons: vec!["A".into(), "B".into(), "C".into()],
+            default_button: Some("1".into()),
+            cancel_button: Some("B".into()),
+            giving_up_after: None,
+        }
+    }
+
+    fn press(x: KeyCode) -> KeyEvent {
+        KeyEvent::new(x, KeyModifiers::NONE)
+    }
+
+    macro_rules! test_form {
+        ($name:ident, $command:expr, $keys:expr, $lines:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let mut form = Form::new(&$command).unwrap();
+                let mut outcome = None;
+                for k in $keys {
+                    outcome = form.key(k);
+                }
+                assert_eq!($lines, form.lines(Some(2)));
+                let got = outcome.map(|x| {
+                    form.record(x)
+                        .map_err(|x| x.downcast::<ScriptError>().unwrap().category)
+                });
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_form!(
+        test_form_dialog_default,
+        dialog(&[], Some("a")),
+        [
+            press(KeyCode::Char('b')),
+            press(KeyCode::Backspace),
+            press(KeyCode::Char('c')),
+            press(KeyCode::Enter),
+        ],
+        vec!["== x ==", "t", "> **", "[Cancel] <OK>", "gives up in 2s"],
+        Some(Ok(
+            json!({"button returned": "OK", "text returned": "ac", "gave up": false})
+        ))
+    );
+    test_form!(
+        test_form_dialog_escape,
+        dialog(&[], None),
+        [press(KeyCode::Left), press(KeyCode::Esc)],
+        vec!["== x ==", "t", "<Cancel> [OK]", "gives up in 2s"],
+        Some(Err(Category::UserCancelled))
+    );
+    test_form!(
+        test_form_dialog_no_cancel_button,
+        dialog(&["A", "B"], None),
+        [press(KeyCode::Esc), press(KeyCode::Tab)],
+        vec!["== x ==", "t", "[A] <B>", "gives up in 2s"],
+        None::<std::result::Result<Json, Category>>
+    );
+    test_form!(
+        test_form_dialog_interrupted,
+        dialog(&["A", "B"], None),
+        [KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)],
+        vec!["== x ==", "t", "[A] <B>", "gives up in 2s"],
+        Some(Err(Category::UserCancelled))
+    );
+    test_form!(
+        test_form_alert,
+        alert(),
+        [
+            press(KeyCode::Right),
+            press(KeyCode::Right),
+            press(KeyCode::Enter)
+        ],
+        vec!["t", "", "m", "[A] [B] <C>", "gives up in 2s"],
+        Some(Ok(json!({"button returned": "C"})))
+    );
+    test_form!(
+        test_form_alert_cancel,
+        alert(),
+        [press(KeyCode::Right), press(KeyCode::Enter)],
+        vec!["t", "", "m", "[A] <B> [C]", "gives up in 2s"],
+        Some(Err(Category::UserCancelled))
+    );
+
+    #[test]
+    fn test_record_gave_up() {
+        let form = Form::new(&dialog(&[], Some("a"))).unwrap();
+        assert_eq!(
+            json!({"button returned": "", "text returned": "a", "gave up": true}),
+            form.record(Outcome::GaveUp).unwrap()
+        );
+    }
+}
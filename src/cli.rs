@@ -1,13 +1,44 @@
-use crate::cmd::Cmd;
-use crate::parse::{Input, Output};
+use crate::backend::alerter::Alerter;
+use crate::backend::dbus::Dbus;
+use crate::backend::linux::{Linux, Tool};
+use crate::backend::logfile::LogFile;
+use crate::backend::mock::Mock;
+use crate::backend::osascript::{Language, Osascript};
+use crate::backend::osc::{Flavor, Osc};
+use crate::backend::route::{Mode, Route};
+use crate::backend::stderr::Stderr;
+use crate::backend::terminal_notifier::TerminalNotifier;
+use crate::backend::tui::{self, Tui};
+use crate::backend::Backend;
+use crate::cmd::Args;
+use crate::color::Color;
+use crate::format::{Field, Format};
+use crate::jxa;
 use crate::response::Data;
-use anyhow::{Error, Result};
-use clap::{self, Parser, Subcommand};
+use crate::script::{Command, Constant, Expr, Handler};
+use crate::status::ExitCodeMap;
+use anyhow::{anyhow, Result};
+use clap::{self, CommandFactory, Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use std::path::{Path, PathBuf};
 
-/// Display a notification, dialog or alert via AppleScript.
+/// Display a notification, dialog or alert via AppleScript or JavaScript for Automation.
 ///
 /// Requirements:
-/// - osascript
+/// - osascript for --backend osascript
+/// - zenity or kdialog, and optionally notify-send for --backend linux
+/// - a notification server on the session bus for --backend dbus
+/// - a controlling terminal for --backend tui
+/// - a terminal that supports notifications by escape sequences for --backend osc
+/// - terminal-notifier for --backend terminal-notifier
+/// - alerter for --backend alerter
+///
+/// Exit status:
+///   0 successfully processed.
+///   1 failed to process.
+///   2 the user pressed the cancel button.
+///   3 no button was returned and the command gave up.
+///   Or the status of the returned button in --exit-code-map.
 ///
 /// Environment variables:
 ///   RUST_LOG
@@ -17,9 +48,99 @@ use clap::{self, Parser, Subcommand};
 #[command(name = "scptdisplay")]
 #[command(version, about)]
 pub struct Cli {
+    /// Backends to run the command, separated by commas.
+    /// Each backend is tried in order until one succeeds or the user cancels.
+    ///   auto: tui if there is no graphical session but a terminal, e.g. over ssh, osascript on macOS, otherwise linux.
+    ///   osascript: run osascript.
+    ///   linux: run notify-send, zenity or kdialog.
+    ///   dbus: call org.freedesktop.Notifications on the session bus, only for notification.
+    ///   tui: render alerts and dialogs in the terminal.
+    ///   osc: write the notification escape sequence to the terminal, only for notification.
+    ///   terminal-notifier: run terminal-notifier, only for notification.
+    ///   alerter: run alerter and wait for the action or the reply, only for notification.
+    ///   stderr: print the message to stderr, alerts and dialogs give up.
+    ///   log: append the message to --log-file, alerts and dialogs give up.
+    ///   mock: return the results written in --mock-script, for testing.
+    #[arg(
+        long = "backend",
+        default_value = "auto",
+        value_delimiter = ',',
+        verbatim_doc_comment
+    )]
+    backend: Vec<BackendKind>,
+    /// Backends to deliver the notification at once, separated by commas, instead of --backend.
+    /// The result is the first success in order. Only for notification.
+    #[arg(
+        long = "fan-out",
+        value_name = "BACKEND",
+        value_delimiter = ',',
+        conflicts_with = "backend",
+        verbatim_doc_comment
+    )]
+    fan_out: Vec<BackendKind>,
+    /// File the log backend appends the messages to, as json lines.
+    #[arg(long = "log-file", value_name = "FILE", verbatim_doc_comment)]
+    log_file: Option<PathBuf>,
+    /// A json list of the results returned by the mock backend, e.g.
+    ///   [{"command": "dialog", "button": "Deploy", "text": "foo"},
+    ///    {"command": "alert", "error": {"message": "User canceled.", "number": -128}}]
+    /// Each run consumes the next result of the command.
+    /// The consumed results are saved in FILE.state, remove it to replay.
+    #[arg(
+        long = "mock-script",
+        value_name = "FILE",
+        required_if_eq_any([("backend", "mock"), ("fan_out", "mock")]),
+        verbatim_doc_comment
+    )]
+    mock_script: Option<PathBuf>,
+    /// Program to display alerts and dialogs by the linux backend.
+    /// Default:
+    ///   kdialog on KDE, otherwise zenity, whichever is installed.
+    #[arg(long = "linux-tool", verbatim_doc_comment)]
+    linux_tool: Option<Tool>,
+    /// Address of the bus used by the dbus backend, e.g. unix:path=/run/user/1000/bus
+    /// Default:
+    ///   The session bus.
+    #[arg(long = "dbus-address", value_name = "ADDRESS", verbatim_doc_comment)]
+    dbus_address: Option<String>,
+    /// Escape sequence written by the osc backend.
+    /// Default:
+    ///   Detected by TERM_PROGRAM, LC_TERMINAL and TERM, osc9 if unknown.
+    #[arg(long = "osc-flavor", verbatim_doc_comment)]
+    osc_flavor: Option<Flavor>,
     /// osascript command.
     #[arg(long = "osascript", default_value = "osascript")]
     osascript: String,
+    /// terminal-notifier command.
+    #[arg(long = "terminal-notifier", default_value = "terminal-notifier")]
+    terminal_notifier: String,
+    /// alerter command.
+    #[arg(long = "alerter", default_value = "alerter")]
+    alerter: String,
+    /// Embed values into the script as literals instead of passing them to osascript as arguments.
+    /// Only for applescript, an error with javascript.
+    #[arg(long = "inline", verbatim_doc_comment)]
+    inline: bool,
+    /// Language of the script run by osascript.
+    ///   applescript: generate an apple script and parse the record printed by osascript.
+    ///   javascript: generate a JavaScript for Automation program that receives options and prints the result as json.
+    #[arg(
+        short = 'l',
+        long = "language",
+        default_value = "applescript",
+        verbatim_doc_comment
+    )]
+    language: Language,
+    /// Exit statuses for the buttons returned by alert or dialog and the actions invoked for notification, e.g. 'Deploy=0,Abort=10'.
+    /// Escape commas and backslashes in the button names by backslashes.
+    #[arg(long = "exit-code-map", verbatim_doc_comment)]
+    exit_code_map: Option<ExitCodeMap>,
+    /// Print the field of the data instead of the whole output.
+    #[arg(long = "print")]
+    print: Option<Field>,
+    /// Format of the output.
+    #[arg(long = "format", default_value = "json")]
+    format: Format,
     #[command(subcommand)]
     command: Commands,
 }
@@ -28,24 +149,32 @@ pub struct Cli {
 pub enum Commands {
     /// Posts a notification using the Notification Center, containing a title, subtitle, and explanation, and optionally playing a sound.
     ///
-    /// Output:
-    ///   A json to stdout.
-    ///     result(string): success for ok, failure for error.
-    ///     code(int or null): exit status of invoked process.
-    ///     error(string or null): stderr of invoked process or null if result is ok.
-    ///     data(map or null): null if result is error.
-    ///       notification(empty map): empty map.
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): notification.
+    ///   activation_type(string or null): how the user interacted with the notification, e.g. actionClicked, replied, closed and timeout.
+    ///   action(string or null): label of the action invoked, null if no action was invoked.
+    ///   reply(string or null): text replied, null if the user did not reply.
     ///
     /// Exit status
     ///   0 successfully processed.
     ///   1 failed to process.
+    ///   3 the notification timed out.
+    ///   Or the status of the invoked action in --exit-code-map.
     ///
     /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW224
-    #[command(about, verbatim_doc_comment, arg_required_else_help = true, visible_aliases = &["n", "notify"])]
+    #[command(
+        about,
+        verbatim_doc_comment,
+        arg_required_else_help = true,
+        subcommand_negates_reqs = true,
+        args_conflicts_with_subcommands = true,
+        visible_aliases = &["n", "notify"]
+    )]
     Notification {
         /// The body text of the notification.
         /// At least one of this and the title must be specified.
-        #[arg(value_name = "TEXT", num_args = 1, value_parser = clap::value_parser!(String), verbatim_doc_comment)]
+        /// Put -- before the text "remove" to post it instead of removing notifications.
+        #[arg(value_name = "TEXT", num_args = 1, value_parser = clap::value_parser!(String), required = true, default_value = "", hide_default_value = true, verbatim_doc_comment)]
         text: String,
         /// The title of the notification.
         /// At least one of this and the body text must be specified.
@@ -58,27 +187,83 @@ pub enum Commands {
         /// This may be the base name of any sound installed in Library/Sounds.
         #[arg(long = "sound", verbatim_doc_comment)]
         sound_name: Option<String>,
+        /// The urgency of the notification.
+        /// Only for the linux and dbus backends.
+        #[arg(long = "urgency", verbatim_doc_comment)]
+        urgency: Option<Urgency>,
+        /// Milliseconds until the notification expires, 0 never expires.
+        /// Only for the linux and dbus backends.
+        /// Default:
+        ///   The notification server decides.
+        #[arg(long = "expire-timeout", value_name = "MS", verbatim_doc_comment)]
+        expire_timeout: Option<i32>,
+        /// The label of an action button, can be repeated.
+        /// Wait until an action is invoked or the notification is closed.
+        /// Only for the dbus and alerter backends, alerter cannot take labels containing commas.
+        #[arg(
+            long = "action",
+            visible_alias = "actions",
+            value_name = "LABEL",
+            conflicts_with = "reply",
+            verbatim_doc_comment
+        )]
+        actions: Vec<String>,
+        /// The label of the button closing the notification.
+        /// Only for the alerter backend.
+        #[arg(long = "close-label", value_name = "LABEL", verbatim_doc_comment)]
+        close_label: Option<String>,
+        /// Display a reply field with the placeholder and wait for the reply.
+        /// Only for the alerter backend.
+        #[arg(
+            long = "reply",
+            value_name = "PLACEHOLDER",
+            num_args = 0..=1,
+            default_missing_value = "",
+            verbatim_doc_comment
+        )]
+        reply: Option<String>,
+        /// Seconds until the notification is closed automatically.
+        /// Only for the alerter backend.
+        #[arg(long = "timeout", value_name = "SECONDS", verbatim_doc_comment)]
+        timeout: Option<u32>,
+        /// The notification replaces the previous notification of the group.
+        /// Only for the terminal-notifier and alerter backends.
+        #[arg(long = "group", value_name = "ID", verbatim_doc_comment)]
+        group: Option<String>,
+        /// The URL opened when the notification is clicked.
+        /// Only for the terminal-notifier backend.
+        #[arg(long = "open", value_name = "URL", verbatim_doc_comment)]
+        open: Option<String>,
+        /// The shell command run when the notification is clicked.
+        /// Only for the terminal-notifier backend.
+        #[arg(long = "execute", value_name = "CMD", verbatim_doc_comment)]
+        execute: Option<String>,
+        /// The bundle identifier of the application activated when the notification is clicked, e.g. com.apple.Terminal
+        /// Only for the terminal-notifier backend.
+        #[arg(long = "activate", value_name = "BUNDLE_ID", verbatim_doc_comment)]
+        activate: Option<String>,
+        /// The URL of the image displayed as the application icon.
+        /// Only for the terminal-notifier and alerter backends.
+        #[arg(long = "app-icon", value_name = "URL", verbatim_doc_comment)]
+        app_icon: Option<String>,
+        #[command(subcommand)]
+        remove: Option<Remove>,
     },
     /// Displays a standardized alert containing a message, explanation, and from one to three buttons.
     ///
-    /// Output:
-    ///   A json to stdout.
-    ///     result(string): success for ok, failure for error.
-    ///     code(int or null): exit status of invoked process.
-    ///     error(string or null): stderr of invoked process or null if result is ok.
-    ///     data(map or null): null if result is error.
-    ///       alert(map):
-    ///         raw(string): raw stdout.
-    ///         record(map(string to string)): parsed stdout.
-    ///         button(string or null): button returned.
-    ///         gave_up(bool): if true, no button was returned and the command gave up.
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): alert.
+    ///   raw(string): raw stdout.
+    ///   record(map): parsed stdout, values keep their types and missing value is null.
+    ///   button(string or null): button returned.
+    ///   gave_up(bool): if true, no button was returned and the command gave up.
     ///
     /// Exit status
     ///   0 successfully processed.
     ///   1 failed to process.
-    ///
-    /// Caution:
-    ///   If you specify a string containing characters such as commas or colons in default_answer or buttons, it may not correctly identify the selected button or text.
+    ///   2 the user pressed the cancel button.
+    ///   3 no button was returned and the command gave up.
+    ///   Or the status of the returned button in --exit-code-map.
     ///
     /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW11
     #[command(about, verbatim_doc_comment, arg_required_else_help = true, visible_aliases = &["a"])]
@@ -99,7 +284,7 @@ pub enum Commands {
             default_value = "informational",
             verbatim_doc_comment
         )]
-        alert_type: Option<String>,
+        alert_type: Option<AlertType>,
         /// A list of up to three button names.
         /// If you supply one name, a button with that name serves as the default and is displayed on the right side of the alert dialog.
         /// If you supply two names, two buttons are displayed on the right, with the second serving as the default button.
@@ -127,25 +312,20 @@ pub enum Commands {
     },
     /// Displays a dialog containing a message, one to three buttons, and optionally an icon and a ﬁeld in which the user can enter text.
     ///
-    /// Output:
-    ///   A json to stdout.
-    ///     result(string): success for ok, failure for error.
-    ///     code(int or null): exit status of invoked process.
-    ///     error(string or null): stderr of invoked process or null if result is ok.
-    ///     data(map or null): null if result is error.
-    ///       dialog(map):
-    ///         raw(string): raw stdout.
-    ///         record(map(string to string)): parsed stdout.
-    ///         text(string or null): text returned.
-    ///         button(string or null): button returned.
-    ///         gave_up(bool): if true, no button was returned and the command gave up.
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): dialog.
+    ///   raw(string): raw stdout.
+    ///   record(map): parsed stdout, values keep their types and missing value is null.
+    ///   text(string or null): text returned.
+    ///   button(string or null): button returned.
+    ///   gave_up(bool): if true, no button was returned and the command gave up.
     ///
     /// Exit status
     ///   0 successfully processed.
     ///   1 failed to process.
-    ///
-    /// Caution:
-    ///   If you specify a string containing characters such as commas or colons in default_answer or buttons, it may not correctly identify the selected button or text.
+    ///   2 the user pressed the cancel button.
+    ///   3 no button was returned and the command gave up.
+    ///   Or the status of the returned button in --exit-code-map.
     ///
     /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW12
     #[command(about, verbatim_doc_comment, arg_required_else_help = true, visible_aliases = &["d"])]
@@ -190,7 +370,7 @@ pub enum Commands {
         ///   stop (or 0): Shows a stop icon
         ///   note (or 1): Shows the application icon
         ///   caution (or 2): Shows a warning icon, badged with the application icon
-        /// An alias or file specifier that specifies a .icns file.
+        /// A POSIX path of a .icns file.
         #[arg(long = "icon", verbatim_doc_comment)]
         icon: Option<String>,
         /// The number of seconds to wait before automatically dismissing the dialog.
@@ -199,36 +379,514 @@ pub enum Commands {
         #[arg(short = 'g', long = "giving_up_after", verbatim_doc_comment)]
         giving_up_after: Option<u8>,
     },
+    /// Allows the user to choose items from a list.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): choose_from_list.
+    ///   items(list of string): items chosen, in the order of the list.
+    ///   cancelled(bool): if true, the user pressed the cancel button and items is empty.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW2
+    #[command(about, verbatim_doc_comment, arg_required_else_help = true, visible_aliases = &["list"])]
+    ChooseFromList {
+        /// The items to choose from.
+        #[arg(value_name = "ITEM", required = true, verbatim_doc_comment)]
+        items: Vec<String>,
+        /// The prompt to be displayed in the dialog.
+        /// Default:
+        ///   "Please make your selection:"
+        #[arg(long = "prompt", verbatim_doc_comment)]
+        prompt: Option<String>,
+        /// The dialog window title.
+        /// Default:
+        ///   None; no title is displayed.
+        #[arg(short = 't', long = "title", verbatim_doc_comment)]
+        title: Option<String>,
+        /// The items initially selected, can be repeated.
+        /// Default:
+        ///   None; no items are selected.
+        #[arg(long = "default_items", value_name = "ITEM", verbatim_doc_comment)]
+        default_items: Vec<String>,
+        /// The name of the OK button.
+        /// Default:
+        ///   "OK"
+        #[arg(long = "ok_button_name", verbatim_doc_comment)]
+        ok_button_name: Option<String>,
+        /// The name of the cancel button.
+        /// Default:
+        ///   "Cancel"
+        #[arg(long = "cancel_button_name", verbatim_doc_comment)]
+        cancel_button_name: Option<String>,
+        /// Allow multiple items to be selected.
+        /// Default:
+        ///   false: only one item can be selected.
+        #[arg(long = "multiple_selections_allowed", verbatim_doc_comment)]
+        multiple_selections_allowed: bool,
+        /// Allow the user to choose OK with no items selected.
+        /// Default:
+        ///   false: at least one item must be selected.
+        #[arg(long = "empty_selection_allowed", verbatim_doc_comment)]
+        empty_selection_allowed: bool,
+    },
+    /// Allows the user to choose files.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): choose_file.
+    ///   paths(list of string): POSIX paths of the files chosen.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW3
+    #[command(about, verbatim_doc_comment)]
+    ChooseFile {
+        /// The prompt to be displayed in the dialog.
+        /// Default:
+        ///   None; no prompt is displayed.
+        #[arg(long = "prompt", verbatim_doc_comment)]
+        prompt: Option<String>,
+        /// A uniform type identifier of the files that can be chosen, e.g. public.image, can be repeated.
+        /// Only for the osascript backend.
+        /// Default:
+        ///   None; any file can be chosen.
+        #[arg(long = "of_type", value_name = "UTI", verbatim_doc_comment)]
+        of_type: Vec<String>,
+        /// The POSIX path of the folder to begin browsing in.
+        /// Default:
+        ///   The folder browsed last time.
+        #[arg(long = "default_location", value_name = "PATH", verbatim_doc_comment)]
+        default_location: Option<PathBuf>,
+        /// Show invisible files and folders.
+        /// Only for the osascript backend.
+        #[arg(long = "invisibles", verbatim_doc_comment)]
+        invisibles: bool,
+        /// Allow multiple files to be chosen.
+        /// Default:
+        ///   false: only one file can be chosen.
+        #[arg(long = "multiple_selections_allowed", verbatim_doc_comment)]
+        multiple_selections_allowed: bool,
+        /// Show the contents of packages, and allow files in them to be chosen.
+        /// Only for the osascript backend.
+        #[arg(long = "showing_package_contents", verbatim_doc_comment)]
+        showing_package_contents: bool,
+    },
+    /// Allows the user to choose folders.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): choose_folder.
+    ///   paths(list of string): POSIX paths of the folders, ending with a slash chosen.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW5
+    #[command(about, verbatim_doc_comment)]
+    ChooseFolder {
+        /// The prompt to be displayed in the dialog.
+        /// Default:
+        ///   None; no prompt is displayed.
+        #[arg(long = "prompt", verbatim_doc_comment)]
+        prompt: Option<String>,
+        /// The POSIX path of the folder to begin browsing in.
+        /// Default:
+        ///   The folder browsed last time.
+        #[arg(long = "default_location", value_name = "PATH", verbatim_doc_comment)]
+        default_location: Option<PathBuf>,
+        /// Show invisible folders.
+        /// Only for the osascript backend.
+        #[arg(long = "invisibles", verbatim_doc_comment)]
+        invisibles: bool,
+        /// Allow multiple folders to be chosen.
+        /// Default:
+        ///   false: only one folder can be chosen.
+        #[arg(long = "multiple_selections_allowed", verbatim_doc_comment)]
+        multiple_selections_allowed: bool,
+        /// Show the contents of packages, and allow folders in them to be chosen.
+        /// Only for the osascript backend.
+        #[arg(long = "showing_package_contents", verbatim_doc_comment)]
+        showing_package_contents: bool,
+    },
+    /// Allows the user to specify a new file name and location, the file is not created.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): choose_file_name.
+    ///   path(string): POSIX path of the file name chosen.
+    ///   exists(bool): if true, the file already exists.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html#//apple_ref/doc/uid/TP40000983-CH216-SW4
+    #[command(about, verbatim_doc_comment)]
+    ChooseFileName {
+        /// The prompt to be displayed near the top of the dialog.
+        /// Default:
+        ///   "Specify new file name and location"
+        #[arg(long = "prompt", verbatim_doc_comment)]
+        prompt: Option<String>,
+        /// The default file name.
+        /// Default:
+        ///   "untitled"
+        #[arg(long = "default_name", value_name = "NAME", verbatim_doc_comment)]
+        default_name: Option<String>,
+        /// The POSIX path of the folder to begin browsing in.
+        /// Default:
+        ///   The folder browsed last time.
+        #[arg(long = "default_location", value_name = "PATH", verbatim_doc_comment)]
+        default_location: Option<PathBuf>,
+    },
+    /// Allows the user to choose a color from a color picker dialog.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): color.
+    ///   components(list of int): red, green and blue of the color chosen, from 0 to 65535.
+    ///   hex(string): the color as #rrggbb.
+    ///   rgb(list of int): red, green and blue, from 0 to 255.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html
+    #[command(about, verbatim_doc_comment)]
+    ChooseColor {
+        /// The color initially selected, e.g. #FF8000.
+        /// Default:
+        ///   Black.
+        #[arg(long = "default", value_name = "#RRGGBB", verbatim_doc_comment)]
+        default: Option<Color>,
+    },
+    /// Speaks the text, or saves the speech to an audio file.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): say.
+    ///   saved_to(string or null): POSIX path of the audio file saved, null if the text was spoken.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///
+    /// See https://developer.apple.com/library/archive/documentation/AppleScript/Conceptual/AppleScriptLangGuide/reference/ASLR_cmds.html
+    #[command(about, verbatim_doc_comment)]
+    Say {
+        /// The text to speak.
+        #[arg(value_name = "TEXT", num_args = 1, value_parser = clap::value_parser!(String), verbatim_doc_comment)]
+        text: String,
+        /// The voice to speak with, see `scptdisplay voices`.
+        /// Default:
+        ///   The system voice.
+        #[arg(long = "voice", verbatim_doc_comment)]
+        voice: Option<String>,
+        /// The speaking rate in words per minute, greater than 0.
+        /// Default:
+        ///   The rate of the voice, about 175.
+        #[arg(long = "rate", value_parser = parse_speaking_rate, verbatim_doc_comment)]
+        rate: Option<f64>,
+        /// The base pitch, from 0 to 127.
+        #[arg(long = "pitch", value_parser = parse_voice_level, verbatim_doc_comment)]
+        pitch: Option<f64>,
+        /// The pitch modulation, from 0 to 127.
+        #[arg(long = "modulation", value_parser = parse_voice_level, verbatim_doc_comment)]
+        modulation: Option<f64>,
+        /// Return without waiting until the speech is completed.
+        #[arg(long = "no-wait", verbatim_doc_comment)]
+        no_wait: bool,
+        /// Save the speech to the AIFF file instead of speaking.
+        #[arg(long = "save-to", value_name = "PATH", verbatim_doc_comment)]
+        save_to: Option<PathBuf>,
+    },
+    /// Lists the voices installed for say, by `say -v ?`.
+    ///
+    /// The voices are listed by say regardless of --backend.
+    ///
+    /// Output data, see `scptdisplay help schema` for the rest of the json:
+    ///   kind(string): voices.
+    ///   voices(list of map): the voices installed.
+    ///     name(string): name of the voice, e.g. Samantha.
+    ///     locale(string): locale of the voice, e.g. en_US.
+    ///     sample(string): a sample text spoken by the voice.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    #[command(about, verbatim_doc_comment)]
+    Voices,
+    /// Chooses lines read from stdin in a list and prints the chosen lines, e.g. `git branch | scptdisplay pick`.
+    ///
+    /// Lines are displayed as text made unique, and printed as they were read even if they are not valid UTF-8.
+    /// Empty lines are skipped.
+    ///
+    /// Output:
+    ///   The chosen lines to stdout in the order they were read, each followed by the separator.
+    ///   --print and --format are ignored.
+    ///   Nothing is printed if cancelled, and the error message is printed to stderr if failed.
+    ///
+    /// Exit status
+    ///   0 successfully processed.
+    ///   1 failed to process.
+    ///   2 the user pressed the cancel button.
+    #[command(about, verbatim_doc_comment)]
+    Pick {
+        /// Read and print lines separated by NUL instead of newline.
+        #[arg(short = '0', long = "null", verbatim_doc_comment)]
+        null: bool,
+        /// Allow multiple lines to be chosen.
+        #[arg(short = 'm', long = "multi", verbatim_doc_comment)]
+        multi: bool,
+        /// Lines matching the regular expression are selected initially, only the first one unless --multi.
+        #[arg(long = "preselect", value_name = "REGEX", verbatim_doc_comment)]
+        preselect: Option<Regex>,
+        /// The prompt to be displayed in the dialog.
+        #[arg(long = "prompt", verbatim_doc_comment)]
+        prompt: Option<String>,
+        /// The dialog window title.
+        #[arg(short = 't', long = "title", verbatim_doc_comment)]
+        title: Option<String>,
+    },
+    /// Prints the json schema of the output.
+    ///
+    /// Output of the other subcommands:
+    ///   A json to stdout by default, in the schema printed by this subcommand.
+    ///     version(int): version of the schema, bumped only by breaking changes.
+    ///     result(string): ok or error.
+    ///     code(int or null): exit status of invoked process.
+    ///     error(map or null): null if result is ok.
+    ///       message(string): error message.
+    ///       number(int or null): apple script error number.
+    ///       range(map or null): range of the script where the error occurred.
+    ///         start(int), end(int)
+    ///       category(string): one of user_cancelled (-128), not_authorized (-1743), timeout (-1712), syntax_error and unknown.
+    ///     data(map or null): null if result is error, the fields are in the help of each subcommand.
+    ///     attempts(list of map): outcomes of the backends that ran the command, in order.
+    ///       backend(string): name of the backend.
+    ///       result(string): ok or error.
+    ///       error(map or null): null if result is ok.
+    #[command(about, verbatim_doc_comment)]
+    Schema,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BackendKind {
+    Auto,
+    Osascript,
+    Linux,
+    Dbus,
+    Tui,
+    #[value(alias = "terminal-osc")]
+    Osc,
+    TerminalNotifier,
+    Alerter,
+    Stderr,
+    Log,
+    Mock,
 }
 
 impl Cli {
-    pub fn cmd(&self) -> Cmd {
-        let mut c = Cmd::new(&self.osascript);
-        let a = self.command.cmd();
-        c.pair("-e", Some(&String::from(a)));
-        c
+    /// Check the combinations of the options that clap cannot express.
+    pub fn validate(&self) -> Result<(), clap::Error> {
+        if self.inline && self.language == Language::JavaScript {
+            return Err(<Cli as CommandFactory>::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "the argument '--inline' cannot be used with '--language javascript'",
+            ));
+        }
+        Ok(())
+    }
+    pub fn command(&self) -> &Commands {
+        &self.command
+    }
+    /// The backends to run the command.
+    pub fn backend(&self) -> Result<Route> {
+        let (mode, kinds) = if self.fan_out.is_empty() {
+            (Mode::Chain, &self.backend)
+        } else if let Commands::Notification { .. } = self.command {
+            (Mode::FanOut, &self.fan_out)
+        } else {
+            return Err(anyhow!("--fan-out is only for notification"));
+        };
+        kinds.iter().try_fold(Route::new(mode), |r, x| {
+            let name = x.to_possible_value().unwrap().get_name().to_string();
+            Ok(r.push(name, self.backend_of(*x)?))
+        })
+    }
+    fn backend_of(&self, kind: BackendKind) -> Result<Box<dyn Backend + Send + Sync>> {
+        Ok(match kind {
+            BackendKind::Auto if tui::is_headless() && tui::has_terminal() => {
+                Box::new(Tui::default())
+            }
+            BackendKind::Auto if cfg!(target_os = "macos") => Box::new(self.osascript()),
+            BackendKind::Auto | BackendKind::Linux => {
+                Box::new(Linux::default().tool(self.linux_tool))
+            }
+            BackendKind::Osascript => Box::new(self.osascript()),
+            BackendKind::Dbus => Box::new(self.dbus()),
+            BackendKind::Tui => Box::new(Tui::default()),
+            BackendKind::Osc => Box::new(Osc::default().flavor(self.osc_flavor)),
+            BackendKind::TerminalNotifier => {
+                Box::new(TerminalNotifier::default().program(&self.terminal_notifier))
+            }
+            BackendKind::Alerter => Box::new(Alerter::default().program(&self.alerter)),
+            BackendKind::Stderr => Box::new(Stderr::default()),
+            BackendKind::Log => match &self.log_file {
+                Some(x) => Box::new(LogFile::new(x)),
+                None => return Err(anyhow!("log backend requires --log-file")),
+            },
+            BackendKind::Mock => Box::new(Mock::new(self.mock_script.as_ref().unwrap())),
+        })
+    }
+    pub fn osascript(&self) -> Osascript {
+        Osascript::default()
+            .program(&self.osascript)
+            .language(self.language)
+            .inline(self.inline)
+    }
+    pub fn dbus(&self) -> Dbus {
+        match &self.dbus_address {
+            Some(x) => Dbus::default().address(x),
+            None => Dbus::default(),
+        }
+    }
+    /// True if the command prints the json schema instead of running a script.
+    pub fn is_schema(&self) -> bool {
+        matches!(self.command, Commands::Schema)
+    }
+    pub fn print(&self) -> Option<Field> {
+        self.print
+    }
+    pub fn format(&self) -> Format {
+        self.format
+    }
+    pub fn exit_code_map(&self) -> ExitCodeMap {
+        self.exit_code_map.clone().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum AlertType {
+    Informational,
+    Warning,
+    Critical,
+}
+
+impl From<AlertType> for Constant {
+    fn from(from: AlertType) -> Self {
+        match from {
+            AlertType::Informational => Constant::Informational,
+            AlertType::Warning => Constant::Warning,
+            AlertType::Critical => Constant::Critical,
+        }
+    }
+}
+
+/// Subcommands of notification.
+#[derive(Debug, Clone, PartialEq, Subcommand)]
+pub enum Remove {
+    /// Removes the notifications of the group posted by the terminal-notifier or alerter backend.
+    #[command(verbatim_doc_comment)]
+    Remove {
+        /// The group of the notifications, ALL removes all the notifications.
+        #[arg(long = "group", value_name = "ID", verbatim_doc_comment)]
+        group: String,
+    },
+}
+
+/// The urgency level of a notification.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+/// A speaking rate, a finite number greater than 0.
+fn speaking_rate(x: f64) -> Result<f64> {
+    if x.is_finite() && x > 0.0 {
+        Ok(x)
+    } else {
+        Err(anyhow!(
+            "invalid speaking rate {x}, expected a finite number greater than 0"
+        ))
+    }
+}
+
+/// A pitch or modulation, a number from 0 to 127.
+fn voice_level(x: f64) -> Result<f64> {
+    if (0.0..=127.0).contains(&x) {
+        Ok(x)
+    } else {
+        Err(anyhow!(
+            "invalid pitch or modulation {x}, expected a number from 0 to 127"
+        ))
     }
-    pub fn parse_stdout(&self, output: Vec<u8>) -> Result<Data> {
-        self.command.parse_stdout(output)
+}
+
+fn parse_speaking_rate(x: &str) -> Result<f64> {
+    speaking_rate(x.parse()?)
+}
+
+fn parse_voice_level(x: &str) -> Result<f64> {
+    voice_level(x.parse()?)
+}
+
+/// True if the icon is a .icns file.
+fn is_icon_file(x: &str) -> bool {
+    x.starts_with('/') || x.ends_with(".icns")
+}
+
+/// An expression of the icon: a constant, a resource id, a file or a resource name.
+fn icon(args: &mut Args, x: &str) -> Expr {
+    match x {
+        "stop" => Expr::Constant(Constant::Stop),
+        "note" => Expr::Constant(Constant::Note),
+        "caution" => Expr::Constant(Constant::Caution),
+        x if is_icon_file(x) => Expr::posix_file(args.text(x)),
+        x => args.integer_or_text(x),
     }
 }
 
 impl Commands {
-    fn cmd(&self) -> Cmd {
+    /// Name of the command.
+    pub fn kind(&self) -> &'static str {
         match self {
+            Commands::Notification { .. } => "notification",
+            Commands::Alert { .. } => "alert",
+            Commands::Dialog { .. } => "dialog",
+            Commands::ChooseFromList { .. } => "choose_from_list",
+            Commands::ChooseFile { .. } => "choose_file",
+            Commands::ChooseFolder { .. } => "choose_folder",
+            Commands::ChooseFileName { .. } => "choose_file_name",
+            Commands::ChooseColor { .. } => "choose_color",
+            Commands::Say { .. } => "say",
+            Commands::Voices => "voices",
+            Commands::Pick { .. } => "pick",
+            Commands::Schema => "schema",
+        }
+    }
+    pub(crate) fn script(&self, args: &mut Args) -> Result<Expr> {
+        let c = match self {
             Commands::Notification {
                 text,
                 title,
                 subtitle,
                 sound_name,
-            } => {
-                let mut c = Cmd::new("display notification");
-                c.arg(Some(Input::quoted(text)));
-                c.pair("with title", title.as_deref().map(Input::quoted));
-                c.pair("subtitle", subtitle.as_deref().map(Input::quoted));
-                c.pair("sound name", sound_name.as_deref().map(Input::quoted));
-                c
-            }
+                ..
+            } => Command::new("display notification")
+                .direct(args.text(text))
+                .param_opt("with title", title.as_deref().map(|x| args.text(x)))
+                .param_opt("subtitle", subtitle.as_deref().map(|x| args.text(x)))
+                .param_opt("sound name", sound_name.as_deref().map(|x| args.text(x))),
             Commands::Alert {
                 text,
                 message,
@@ -237,25 +895,23 @@ impl Commands {
                 default_button,
                 cancel_button,
                 giving_up_after,
-            } => {
-                let mut c = Cmd::new("display alert");
-                c.arg(Some(Input::quoted(text)));
-                c.pair("message", message.as_deref().map(Input::quoted));
-                c.pair("as", alert_type.as_deref());
-                if !buttons.is_empty() {
-                    c.pair("buttons", Some(Input::apple_script_list(buttons.clone())));
-                }
-                c.pair(
+            } => Command::new("display alert")
+                .direct(args.text(text))
+                .param_opt("message", message.as_deref().map(|x| args.text(x)))
+                .param_opt("as", alert_type.map(|x| Expr::Constant(x.into())))
+                .param_opt("buttons", (!buttons.is_empty()).then(|| args.list(buttons)))
+                .param_opt(
                     "default button",
-                    default_button.as_deref().map(Input::integer_or_text),
-                );
-                c.pair(
+                    default_button.as_deref().map(|x| args.integer_or_text(x)),
+                )
+                .param_opt(
                     "cancel button",
-                    cancel_button.as_deref().map(Input::integer_or_text),
-                );
-                c.pair("giving up after", giving_up_after.map(|x| format!("{x}")));
-                c
-            }
+                    cancel_button.as_deref().map(|x| args.integer_or_text(x)),
+                )
+                .param_opt(
+                    "giving up after",
+                    giving_up_after.map(|x| Expr::Integer(x.into())),
+                ),
             Commands::Dialog {
                 text,
                 default_answer,
@@ -264,73 +920,794 @@ impl Commands {
                 default_button,
                 cancel_button,
                 title,
-                icon,
+                icon: icon_name,
                 giving_up_after,
-            } => {
-                let mut c = Cmd::new("display dialog");
-                c.arg(Some(Input::quoted(text)));
-                c.pair(
+            } => Command::new("display dialog")
+                .direct(args.text(text))
+                .param_opt(
                     "default answer",
-                    default_answer.as_deref().map(Input::quoted),
-                );
-                c.arg(hidden_answer.then_some("hidden answer"));
-                if !buttons.is_empty() {
-                    c.pair("buttons", Some(Input::apple_script_list(buttons.clone())));
-                }
-                c.pair(
+                    default_answer.as_deref().map(|x| args.text(x)),
+                )
+                .param_opt(
+                    "hidden answer",
+                    hidden_answer.then_some(Expr::Boolean(true)),
+                )
+                .param_opt("buttons", (!buttons.is_empty()).then(|| args.list(buttons)))
+                .param_opt(
                     "default button",
-                    default_button.as_deref().map(Input::integer_or_text),
-                );
-                c.pair(
+                    default_button.as_deref().map(|x| args.integer_or_text(x)),
+                )
+                .param_opt(
                     "cancel button",
-                    cancel_button.as_deref().map(Input::integer_or_text),
-                );
-                c.pair("with title", title.as_deref().map(Input::quoted));
-                c.pair("with icon", icon.as_deref().map(Input::integer_or_text));
-                c.pair("giving up after", giving_up_after.map(|x| format!("{x}")));
-                c
+                    cancel_button.as_deref().map(|x| args.integer_or_text(x)),
+                )
+                .param_opt("with title", title.as_deref().map(|x| args.text(x)))
+                .param_opt("with icon", icon_name.as_deref().map(|x| icon(args, x)))
+                .param_opt(
+                    "giving up after",
+                    giving_up_after.map(|x| Expr::Integer(x.into())),
+                ),
+            Commands::ChooseFromList {
+                items,
+                prompt,
+                title,
+                default_items,
+                ok_button_name,
+                cancel_button_name,
+                multiple_selections_allowed,
+                empty_selection_allowed,
+            } => Command::new("choose from list")
+                .direct(args.lines(items))
+                .param_opt("with prompt", prompt.as_deref().map(|x| args.text(x)))
+                .param_opt("with title", title.as_deref().map(|x| args.text(x)))
+                .param_opt(
+                    "default items",
+                    (!default_items.is_empty()).then(|| args.list(default_items)),
+                )
+                .param_opt(
+                    "OK button name",
+                    ok_button_name.as_deref().map(|x| args.text(x)),
+                )
+                .param_opt(
+                    "cancel button name",
+                    cancel_button_name.as_deref().map(|x| args.text(x)),
+                )
+                .param_opt(
+                    "multiple selections allowed",
+                    multiple_selections_allowed.then_some(Expr::Boolean(true)),
+                )
+                .param_opt(
+                    "empty selection allowed",
+                    empty_selection_allowed.then_some(Expr::Boolean(true)),
+                ),
+            Commands::ChooseFile {
+                prompt,
+                of_type,
+                default_location,
+                invisibles,
+                multiple_selections_allowed,
+                showing_package_contents,
+            } => {
+                let c = Command::new("choose file")
+                    .param_opt("with prompt", prompt.as_deref().map(|x| args.text(x)))
+                    .param_opt("of type", (!of_type.is_empty()).then(|| args.list(of_type)))
+                    .param_opt(
+                        "default location",
+                        default_location
+                            .as_deref()
+                            .map(|x| Expr::alias(args.text(&x.to_string_lossy()))),
+                    )
+                    .param_opt("invisibles", invisibles.then_some(Expr::Boolean(true)))
+                    .param_opt(
+                        "multiple selections allowed",
+                        multiple_selections_allowed.then_some(Expr::Boolean(true)),
+                    )
+                    .param_opt(
+                        "showing package contents",
+                        showing_package_contents.then_some(Expr::Boolean(true)),
+                    );
+                return Ok(args.call(Handler::PosixPaths, c.into()));
             }
-        }
+            Commands::ChooseFolder {
+                prompt,
+                default_location,
+                invisibles,
+                multiple_selections_allowed,
+                showing_package_contents,
+            } => {
+                let c = Command::new("choose folder")
+                    .param_opt("with prompt", prompt.as_deref().map(|x| args.text(x)))
+                    .param_opt(
+                        "default location",
+                        default_location
+                            .as_deref()
+                            .map(|x| Expr::alias(args.text(&x.to_string_lossy()))),
+                    )
+                    .param_opt("invisibles", invisibles.then_some(Expr::Boolean(true)))
+                    .param_opt(
+                        "multiple selections allowed",
+                        multiple_selections_allowed.then_some(Expr::Boolean(true)),
+                    )
+                    .param_opt(
+                        "showing package contents",
+                        showing_package_contents.then_some(Expr::Boolean(true)),
+                    );
+                return Ok(args.call(Handler::PosixPaths, c.into()));
+            }
+            Commands::ChooseFileName {
+                prompt,
+                default_name,
+                default_location,
+            } => {
+                let c = Command::new("choose file name")
+                    .param_opt("with prompt", prompt.as_deref().map(|x| args.text(x)))
+                    .param_opt(
+                        "default name",
+                        default_name.as_deref().map(|x| args.text(x)),
+                    )
+                    .param_opt(
+                        "default location",
+                        default_location
+                            .as_deref()
+                            .map(|x| Expr::alias(args.text(&x.to_string_lossy()))),
+                    );
+                return Ok(args.call(Handler::PosixPaths, c.into()));
+            }
+            Commands::ChooseColor { default } => Command::new("choose color").param_opt(
+                "default color",
+                default.map(|x| Expr::List(x.0.map(|c| Expr::Integer(c.into())).into())),
+            ),
+            Commands::Say {
+                text,
+                voice,
+                rate,
+                pitch,
+                modulation,
+                no_wait,
+                save_to,
+            } => Command::new("say")
+                .direct(args.text(text))
+                .param_opt("using", voice.as_deref().map(|x| args.text(x)))
+                .param_opt(
+                    "speaking rate",
+                    rate.map(speaking_rate).transpose()?.map(Expr::Real),
+                )
+                .param_opt("pitch", pitch.map(voice_level).transpose()?.map(Expr::Real))
+                .param_opt(
+                    "modulation",
+                    modulation.map(voice_level).transpose()?.map(Expr::Real),
+                )
+                .param_opt(
+                    "waiting until completion",
+                    no_wait.then_some(Expr::Boolean(false)),
+                )
+                .param_opt(
+                    "saving to",
+                    save_to
+                        .as_deref()
+                        .map(|x| Expr::posix_file(args.text(&x.to_string_lossy()))),
+                ),
+            Commands::Voices => return Err(anyhow!("voices runs say, not a script")),
+            Commands::Pick { .. } => {
+                return Err(anyhow!(
+                    "pick runs choose from list, convert it by Lines::command"
+                ))
+            }
+            Commands::Schema => return Err(anyhow!("schema runs no script")),
+        };
+        Ok(c.into())
     }
-    fn parse_stdout(&self, output: Vec<u8>) -> Result<Data> {
-        match self {
-            Commands::Notification { .. } => Ok(Data::Notification {}),
-            Commands::Dialog { .. } => match String::from_utf8(output) {
-                Err(err) => Err(Error::new(err)),
-                Ok(x) => {
-                    let r = Output::record(&x);
-                    let text = r.get("text returned").cloned();
-                    let button = r.get("button returned").cloned();
-                    let gave_up = match r.get("gave up") {
-                        None => false,
-                        Some(x) => x == "true",
-                    };
-                    Ok(Data::Dialog {
-                        raw: x,
-                        record: r,
-                        text,
-                        button,
-                        gave_up,
-                    })
+    pub(crate) fn jxa(&self) -> Result<jxa::Request> {
+        Ok(match self {
+            Commands::Notification {
+                text,
+                title,
+                subtitle,
+                sound_name,
+                ..
+            } => jxa::Request::new("displayNotification", text)
+                .option("withTitle", title.as_deref())
+                .option("subtitle", subtitle.as_deref())
+                .option("soundName", sound_name.as_deref()),
+            Commands::Alert {
+                text,
+                message,
+                alert_type,
+                buttons,
+                default_button,
+                cancel_button,
+                giving_up_after,
+            } => jxa::Request::new("displayAlert", text)
+                .option("message", message.as_deref())
+                .option("as", alert_type.map(|x| Constant::from(x).to_string()))
+                .option("buttons", (!buttons.is_empty()).then_some(buttons.clone()))
+                .option(
+                    "defaultButton",
+                    default_button.as_deref().map(integer_or_text),
+                )
+                .option(
+                    "cancelButton",
+                    cancel_button.as_deref().map(integer_or_text),
+                )
+                .option("givingUpAfter", *giving_up_after),
+            Commands::Dialog {
+                text,
+                default_answer,
+                hidden_answer,
+                buttons,
+                default_button,
+                cancel_button,
+                title,
+                icon: icon_name,
+                giving_up_after,
+            } => {
+                let is_path = icon_name.as_deref().is_some_and(is_icon_file);
+                jxa::Request::new("displayDialog", text)
+                    .option("defaultAnswer", default_answer.as_deref())
+                    .option("hiddenAnswer", hidden_answer.then_some(true))
+                    .option("buttons", (!buttons.is_empty()).then_some(buttons.clone()))
+                    .option(
+                        "defaultButton",
+                        default_button.as_deref().map(integer_or_text),
+                    )
+                    .option(
+                        "cancelButton",
+                        cancel_button.as_deref().map(integer_or_text),
+                    )
+                    .option("withTitle", title.as_deref())
+                    .option(
+                        "withIcon",
+                        icon_name
+                            .as_deref()
+                            .filter(|_| !is_path)
+                            .map(integer_or_text),
+                    )
+                    .path("withIcon", icon_name.as_deref().filter(|_| is_path))
+                    .option("givingUpAfter", *giving_up_after)
+            }
+            Commands::ChooseFromList {
+                items,
+                prompt,
+                title,
+                default_items,
+                ok_button_name,
+                cancel_button_name,
+                multiple_selections_allowed,
+                empty_selection_allowed,
+            } => jxa::Request::list("chooseFromList", items)
+                .option("withPrompt", prompt.as_deref())
+                .option("withTitle", title.as_deref())
+                .option(
+                    "defaultItems",
+                    (!default_items.is_empty()).then_some(default_items.clone()),
+                )
+                .option("okButtonName", ok_button_name.as_deref())
+                .option("cancelButtonName", cancel_button_name.as_deref())
+                .option(
+                    "multipleSelectionsAllowed",
+                    multiple_selections_allowed.then_some(true),
+                )
+                .option(
+                    "emptySelectionAllowed",
+                    empty_selection_allowed.then_some(true),
+                ),
+            Commands::ChooseFile {
+                prompt,
+                of_type,
+                default_location,
+                invisibles,
+                multiple_selections_allowed,
+                showing_package_contents,
+            } => jxa::Request::without_text("chooseFile")
+                .option("withPrompt", prompt.as_deref())
+                .option("ofType", (!of_type.is_empty()).then_some(of_type.clone()))
+                .path(
+                    "defaultLocation",
+                    default_location
+                        .as_deref()
+                        .map(|x| x.to_string_lossy().into_owned()),
+                )
+                .option("invisibles", invisibles.then_some(true))
+                .option(
+                    "multipleSelectionsAllowed",
+                    multiple_selections_allowed.then_some(true),
+                )
+                .option(
+                    "showingPackageContents",
+                    showing_package_contents.then_some(true),
+                )
+                .posix_paths(),
+            Commands::ChooseFolder {
+                prompt,
+                default_location,
+                invisibles,
+                multiple_selections_allowed,
+                showing_package_contents,
+            } => jxa::Request::without_text("chooseFolder")
+                .option("withPrompt", prompt.as_deref())
+                .path(
+                    "defaultLocation",
+                    default_location
+                        .as_deref()
+                        .map(|x| x.to_string_lossy().into_owned()),
+                )
+                .option("invisibles", invisibles.then_some(true))
+                .option(
+                    "multipleSelectionsAllowed",
+                    multiple_selections_allowed.then_some(true),
+                )
+                .option(
+                    "showingPackageContents",
+                    showing_package_contents.then_some(true),
+                )
+                .posix_paths(),
+            Commands::ChooseFileName {
+                prompt,
+                default_name,
+                default_location,
+            } => jxa::Request::without_text("chooseFileName")
+                .option("withPrompt", prompt.as_deref())
+                .option("defaultName", default_name.as_deref())
+                .path(
+                    "defaultLocation",
+                    default_location
+                        .as_deref()
+                        .map(|x| x.to_string_lossy().into_owned()),
+                )
+                .posix_paths(),
+            Commands::ChooseColor { default } => jxa::Request::without_text("chooseColor")
+                .option("defaultColor", default.map(|x| x.reals().to_vec()))
+                .rgb_color(),
+            Commands::Say {
+                text,
+                voice,
+                rate,
+                pitch,
+                modulation,
+                no_wait,
+                save_to,
+            } => jxa::Request::new("say", text)
+                .option("using", voice.as_deref())
+                .option("speakingRate", rate.map(speaking_rate).transpose()?)
+                .option("pitch", pitch.map(voice_level).transpose()?)
+                .option("modulation", modulation.map(voice_level).transpose()?)
+                .option("waitingUntilCompletion", no_wait.then_some(false))
+                .path(
+                    "savingTo",
+                    save_to.as_deref().map(|x| x.to_string_lossy().into_owned()),
+                ),
+            Commands::Voices => return Err(anyhow!("voices runs say, not a script")),
+            Commands::Pick { .. } => {
+                return Err(anyhow!(
+                    "pick runs choose from list, convert it by Lines::command"
+                ))
+            }
+            Commands::Schema => return Err(anyhow!("schema runs no script")),
+        })
+    }
+    /// Convert the output and its record into the result.
+    pub(crate) fn data(&self, raw: String, record: serde_json::Value) -> Result<Data> {
+        Ok(match self {
+            Commands::Notification { .. } => Data::Notification {
+                activation_type: record["activation type"].as_str().map(String::from),
+                action: record["action"].as_str().map(String::from),
+                reply: record["reply"].as_str().map(String::from),
+            },
+            Commands::Dialog { .. } => {
+                let text = record["text returned"].as_str().map(String::from);
+                let button = record["button returned"].as_str().map(String::from);
+                let gave_up = record["gave up"].as_bool().unwrap_or(false);
+                Data::Dialog {
+                    raw,
+                    record,
+                    text,
+                    button,
+                    gave_up,
+                }
+            }
+            Commands::Alert { .. } => {
+                let button = record["button returned"].as_str().map(String::from);
+                let gave_up = record["gave up"].as_bool().unwrap_or(false);
+                Data::Alert {
+                    raw,
+                    record,
+                    button,
+                    gave_up,
                 }
+            }
+            Commands::ChooseFromList {
+                empty_selection_allowed,
+                ..
+            } => {
+                let items = texts(&record);
+                // false is returned by the cancel button,
+                // and nothing can be chosen unless an empty selection is allowed
+                let cancelled = record == false || (items.is_empty() && !empty_selection_allowed);
+                Data::ChooseFromList { items, cancelled }
+            }
+            Commands::ChooseFile { .. } => Data::ChooseFile {
+                paths: texts(&record),
+            },
+            Commands::ChooseFolder { .. } => Data::ChooseFolder {
+                paths: texts(&record),
             },
-            Commands::Alert { .. } => match String::from_utf8(output) {
-                Err(err) => Err(Error::new(err)),
-                Ok(x) => {
-                    let r = Output::record(&x);
-                    let button = r.get("button returned").cloned();
-                    let gave_up = match r.get("gave up") {
-                        None => false,
-                        Some(x) => x == "true",
-                    };
-                    Ok(Data::Alert {
-                        raw: x,
-                        record: r,
-                        button,
-                        gave_up,
-                    })
+            Commands::ChooseFileName { .. } => {
+                let path = texts(&record)
+                    .into_iter()
+                    .next()
+                    .filter(|x| !x.is_empty())
+                    .ok_or_else(|| anyhow!("no file name chosen: {record}"))?;
+                let exists = Path::new(&path).exists();
+                Data::ChooseFileName { path, exists }
+            }
+            Commands::ChooseColor { .. } => {
+                let color =
+                    Color::from_json(&record).ok_or_else(|| anyhow!("not a color: {record}"))?;
+                Data::Color {
+                    components: color.0,
+                    hex: color.hex(),
+                    rgb: color.rgb(),
                 }
+            }
+            Commands::Say { save_to, .. } => Data::Say {
+                saved_to: save_to.as_deref().map(|x| x.to_string_lossy().into_owned()),
+            },
+            Commands::Voices => return Err(anyhow!("voices runs say, not a script")),
+            Commands::Pick { .. } => {
+                return Err(anyhow!(
+                    "pick runs choose from list, convert it by Lines::command"
+                ))
+            }
+            Commands::Schema => return Err(anyhow!("schema runs no script")),
+        })
+    }
+}
+
+/// The items of the list as texts, empty if x is not a list.
+fn texts(x: &serde_json::Value) -> Vec<String> {
+    x.as_array()
+        .map(|xs| {
+            xs.iter()
+                .map(|x| match x {
+                    serde_json::Value::String(x) => x.clone(),
+                    x => x.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A json value of the integer if x is an integer, otherwise the text.
+fn integer_or_text(x: &str) -> serde_json::Value {
+    match x.parse::<u8>() {
+        Ok(n) => n.into(),
+        Err(_) => x.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stdout_dialog() {
+        let cli = Cli::parse_from(["scptdisplay", "dialog", "x", "--default_answer", ""]);
+        let got = cli
+            .osascript()
+            .parse_stdout(
+                cli.command(),
+                br#"{button returned:"OK", text returned:"a, b:c", gave up:true}"#.to_vec(),
+            )
+            .unwrap();
+        match got {
+            Data::Dialog {
+                record,
+                text,
+                button,
+                gave_up,
+                ..
+            } => {
+                assert_eq!(Some("a, b:c".to_string()), text);
+                assert_eq!(Some("OK".to_string()), button);
+                assert!(gave_up);
+                assert_eq!(serde_json::Value::Bool(true), record["gave up"]);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_stdout_dialog_javascript() {
+        let cli = Cli::parse_from(["scptdisplay", "-l", "javascript", "dialog", "x"]);
+        let got = cli
+            .osascript()
+            .parse_stdout(
+                cli.command(),
+                br#"{"buttonReturned":"OK","textReturned":"a","gaveUp":false}"#.to_vec(),
+            )
+            .unwrap();
+        match got {
+            Data::Dialog {
+                text,
+                button,
+                gave_up,
+                ..
+            } => {
+                assert_eq!(Some("a".to_string()), text);
+                assert_eq!(Some("OK".to_string()), button);
+                assert!(!gave_up);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_stdout_choose_from_list() {
+        let cli = Cli::parse_from([
+            "scptdisplay",
+            "choose-from-list",
+            "a, b",
+            "c",
+            "--multiple_selections_allowed",
+        ]);
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), br#"{"a, b", "c"}"#.to_vec())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFromList {
+                items: vec!["a, b".into(), "c".into()],
+                cancelled: false
+            },
+            got
+        );
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), b"false\n".to_vec())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFromList {
+                items: vec![],
+                cancelled: true
+            },
+            got
+        );
+    }
+
+    #[test]
+    fn test_parse_stdout_choose_from_list_javascript() {
+        let cli = Cli::parse_from([
+            "scptdisplay",
+            "-l",
+            "javascript",
+            "choose-from-list",
+            "a",
+            "b",
+            "--empty_selection_allowed",
+        ]);
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), b"[]".to_vec())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFromList {
+                items: vec![],
+                cancelled: false
+            },
+            got
+        );
+    }
+
+    #[test]
+    fn test_parse_stdout_choose_file() {
+        let cli = Cli::parse_from(["scptdisplay", "choose-file", "--of_type", "public.png"]);
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), br#"{"/tmp/a, b.png"}"#.to_vec())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFile {
+                paths: vec!["/tmp/a, b.png".into()]
+            },
+            got
+        );
+    }
+
+    #[test]
+    fn test_parse_stdout_choose_file_name() {
+        let cli = Cli::parse_from(["scptdisplay", "choose-file-name", "--default_name", "x"]);
+        let dir = std::env::temp_dir();
+        let stdout = format!("{{{:?}}}", dir.to_string_lossy());
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), stdout.into_bytes())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFileName {
+                path: dir.to_string_lossy().into_owned(),
+                exists: true
+            },
+            got
+        );
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), br#"{"/nonexistent/report.csv"}"#.to_vec())
+            .unwrap();
+        assert_eq!(
+            Data::ChooseFileName {
+                path: "/nonexistent/report.csv".into(),
+                exists: false
+            },
+            got
+        );
+        assert!(cli
+            .osascript()
+            .parse_stdout(cli.command(), b"{}\n".to_vec())
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_stdout_choose_color() {
+        let cli = Cli::parse_from(["scptdisplay", "choose-color", "--default", "#ff8000"]);
+        let want = Data::Color {
+            components: [65535, 32896, 0],
+            hex: "#ff8000".into(),
+            rgb: [255, 128, 0],
+        };
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), b"{65535, 32896, 0}\n".to_vec())
+            .unwrap();
+        assert_eq!(want, got);
+        let cli = Cli::parse_from(["scptdisplay", "-l", "javascript", "choose-color"]);
+        let got = cli
+            .osascript()
+            .parse_stdout(cli.command(), b"[65535,32896,0]\n".to_vec())
+            .unwrap();
+        assert_eq!(want, got);
+        assert!(cli
+            .osascript()
+            .parse_stdout(cli.command(), b"{}\n".to_vec())
+            .is_err());
+        assert!(Cli::try_parse_from(["scptdisplay", "choose-color", "--default", "red"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_say_invalid() {
+        for (k, v) in [
+            ("--rate", "NaN"),
+            ("--rate", "inf"),
+            ("--rate", "0"),
+            ("--rate", "-1"),
+            ("--pitch", "500"),
+            ("--pitch", "-1"),
+            ("--pitch", "NaN"),
+            ("--modulation", "127.5"),
+            ("--modulation", "x"),
+        ] {
+            assert!(Cli::try_parse_from(["scptdisplay", "say", "x", k, v]).is_err());
+        }
+        assert!(Cli::try_parse_from(["scptdisplay", "say", "x", "--pitch", "127"]).is_ok());
+        assert!(Cli::try_parse_from(["scptdisplay", "say", "x", "--modulation", "0"]).is_ok());
+        // built without parsing, e.g. by the library
+        let say = Commands::Say {
+            text: "x".into(),
+            voice: None,
+            rate: Some(f64::NAN),
+            pitch: None,
+            modulation: None,
+            no_wait: false,
+            save_to: None,
+        };
+        assert!(say.script(&mut Args::new(true)).is_err());
+        assert!(say.jxa().is_err());
+    }
+
+    #[test]
+    fn test_say_script() {
+        let cli = Cli::parse_from([
+            "scptdisplay",
+            "say",
+            "Build finished",
+            "--voice",
+            "Samantha",
+            "--rate",
+            "200",
+            "--pitch",
+            "45.5",
+            "--no-wait",
+            "--save-to",
+            "/tmp/x.aiff",
+        ]);
+        let mut args = Args::new(true);
+        let got = cli.command().script(&mut args).unwrap().to_string();
+        assert_eq!(
+            r#"(say "Build finished" using "Samantha" speaking rate 200 pitch 45.5 waiting until completion false saving to (POSIX file "/tmp/x.aiff"))"#,
+            got
+        );
+        let got = cli.osascript().parse_stdout(cli.command(), vec![]).unwrap();
+        assert_eq!(
+            Data::Say {
+                saved_to: Some("/tmp/x.aiff".into())
             },
+            got
+        );
+    }
+
+    #[test]
+    fn test_parse_notification_actions() {
+        let cli = Cli::parse_from([
+            "scptdisplay",
+            "notification",
+            "x",
+            "--action",
+            "Yes, deploy",
+            "--actions",
+            "No",
+        ]);
+        match cli.command() {
+            Commands::Notification { actions, .. } => {
+                assert_eq!(vec!["Yes, deploy", "No"], *actions)
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+        assert!(Cli::try_parse_from([
+            "scptdisplay",
+            "notification",
+            "x",
+            "--action",
+            "Yes",
+            "--reply"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_notification_remove() {
+        let cli = Cli::parse_from(["scptdisplay", "notification", "remove", "--group", "ci"]);
+        match cli.command() {
+            Commands::Notification { remove, .. } => assert_eq!(
+                Some(&Remove::Remove { group: "ci".into() }),
+                remove.as_ref()
+            ),
+            x => panic!("unexpected {x:?}"),
+        }
+        // the text remove is posted after --
+        let cli = Cli::parse_from(["scptdisplay", "notification", "--", "remove"]);
+        match cli.command() {
+            Commands::Notification { text, remove, .. } => {
+                assert_eq!("remove", text);
+                assert_eq!(None, remove.as_ref());
+            }
+            x => panic!("unexpected {x:?}"),
         }
+        assert!(Cli::try_parse_from(["scptdisplay", "notification"]).is_err());
+        assert!(Cli::try_parse_from(["scptdisplay", "notification", "remove"]).is_err());
+        assert!(Cli::try_parse_from(["scptdisplay", "notification", "-t", "x"]).is_err());
+        assert!(Cli::try_parse_from([
+            "scptdisplay",
+            "notification",
+            "x",
+            "remove",
+            "--group",
+            "ci"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_backend_fan_out() {
+        let cli = Cli::parse_from(["scptdisplay", "--fan-out", "stderr", "notification", "x"]);
+        assert!(cli.backend().is_ok());
+        let cli = Cli::parse_from(["scptdisplay", "--fan-out", "stderr", "alert", "x"]);
+        assert!(cli.backend().is_err());
+    }
+
+    #[test]
+    fn test_validate_inline() {
+        let cli = Cli::parse_from(["scptdisplay", "--inline", "dialog", "x"]);
+        assert!(cli.validate().is_ok());
+        let cli = Cli::parse_from(["scptdisplay", "--inline", "-l", "javascript", "dialog", "x"]);
+        assert!(cli.validate().is_err());
     }
 }
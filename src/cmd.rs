@@ -1,3 +1,4 @@
+use crate::script::{Expr, Handler, Script};
 use std::convert::{From, Into};
 use std::process::Command;
 
@@ -24,6 +25,85 @@ impl Cmd {
     }
 }
 
+/// The first argument passed to the script.
+///
+/// Values never come first, so osascript does not mistake a value starting with `-` for its option.
+const ARGV_HEAD: &str = "scptdisplay";
+
+/// Values referred from the script.
+///
+/// By default, values are passed to osascript as arguments and the script refers to them as items of argv,
+/// so that no value is parsed as apple script.
+/// If inline, values are embedded into the script as literals.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Args {
+    inline: bool,
+    values: Vec<String>,
+    /// Handlers called by the script.
+    handlers: Vec<Handler>,
+}
+
+impl Args {
+    pub fn new(inline: bool) -> Args {
+        let values = if inline {
+            vec![]
+        } else {
+            vec![ARGV_HEAD.into()]
+        };
+        Args {
+            inline,
+            values,
+            handlers: vec![],
+        }
+    }
+    /// Arguments for the script.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+    /// An expression evaluated to the text.
+    pub fn text(&mut self, x: &str) -> Expr {
+        if self.inline {
+            Expr::text(x)
+        } else {
+            self.values.push(x.into());
+            Expr::Argv(self.values.len())
+        }
+    }
+    /// An expression evaluated to the integer if x is an integer, otherwise the text.
+    pub fn integer_or_text(&mut self, x: &str) -> Expr {
+        match x.parse::<u8>() {
+            Ok(n) => Expr::Integer(n.into()),
+            Err(_) => self.text(x),
+        }
+    }
+    /// An expression evaluated to the list of the texts.
+    pub fn list<S: AsRef<str>>(&mut self, v: &[S]) -> Expr {
+        Expr::List(v.iter().map(|x| self.text(x.as_ref())).collect())
+    }
+    /// An expression evaluated to the list of the texts, which are passed as one argument of lines if possible,
+    /// so that thousands of texts do not exceed the limit of the arguments.
+    pub fn lines<S: AsRef<str>>(&mut self, v: &[S]) -> Expr {
+        // paragraphs of also breaks at CR and the unicode separators, and drops a trailing empty line
+        let is_line = |x: &str| !x.is_empty() && !x.contains(['\n', '\r', '\u{2028}', '\u{2029}']);
+        if self.inline || !v.iter().all(|x| is_line(x.as_ref())) {
+            return self.list(v);
+        }
+        let xs: Vec<&str> = v.iter().map(|x| x.as_ref()).collect();
+        Expr::paragraphs(self.text(&xs.join("\n")))
+    }
+    /// An expression calling the handler, which is defined in the script.
+    pub fn call(&mut self, h: Handler, x: Expr) -> Expr {
+        self.handlers.push(h);
+        Expr::Call(h, Box::new(x))
+    }
+    /// A script running the command, which can refer the arguments and call the handlers.
+    pub fn script<E: Into<Expr>>(&self, x: E) -> Script {
+        self.handlers
+            .iter()
+            .fold(Script::new(!self.inline).push(x), |s, h| s.handler(*h))
+    }
+}
+
 impl From<Cmd> for String {
     fn from(from: Cmd) -> Self {
         from.0
@@ -48,6 +128,7 @@ impl From<Cmd> for Command {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::script::Command as ScriptCommand;
 
     #[test]
     fn test_cmd_init() {
@@ -65,6 +146,69 @@ mod tests {
         assert_eq!(want, got.0);
     }
 
+    #[test]
+    fn test_args_inline() {
+        let mut got = Args::new(true);
+        assert_eq!(Expr::text("a\"b"), got.text("a\"b"));
+        assert_eq!(Expr::Integer(1), got.integer_or_text("1"));
+        assert_eq!(
+            Expr::List(vec![Expr::text("x"), Expr::text("y")]),
+            got.list(&["x", "y"])
+        );
+        let script = got.script(ScriptCommand::new("beep"));
+        assert_eq!("beep", format!("{script}"));
+        assert!(got.values().is_empty());
+    }
+
+    #[test]
+    fn test_args_argv() {
+        let mut got = Args::new(false);
+        assert_eq!(Expr::Argv(2), got.text("a\"b"));
+        assert_eq!(Expr::Integer(1), got.integer_or_text("1"));
+        assert_eq!(Expr::Argv(3), got.integer_or_text("OK"));
+        assert_eq!(
+            Expr::List(vec![Expr::Argv(4), Expr::Argv(5)]),
+            got.list(&["-x", "y"])
+        );
+        let script = got.script(ScriptCommand::new("beep"));
+        assert_eq!("on run argv\n    beep\nend run", format!("{script}"));
+        assert_eq!(vec![ARGV_HEAD, "a\"b", "OK", "-x", "y"], got.values());
+    }
+
+    #[test]
+    fn test_args_lines() {
+        let mut got = Args::new(false);
+        assert_eq!(Expr::paragraphs(Expr::Argv(2)), got.lines(&["a", "-b"]));
+        assert_eq!(
+            Expr::List(vec![Expr::Argv(3), Expr::Argv(4)]),
+            got.lines(&["c\r", "d"])
+        );
+        assert_eq!(vec![ARGV_HEAD, "a\n-b", "c\r", "d"], got.values());
+        let mut got = Args::new(true);
+        assert_eq!(Expr::List(vec![Expr::text("a")]), got.lines(&["a"]));
+    }
+
+    #[test]
+    fn test_cmd_args_command() {
+        let mut args = Args::new(false);
+        let mut c = Cmd::new("osascript");
+        let text = args.text("; quit");
+        let script = args.script(ScriptCommand::new("display dialog").direct(text));
+        c.pair("-e", Some(format!("{script}")));
+        for x in args.values() {
+            c.arg(Some(x));
+        }
+        let got = Command::from(c);
+        let want = vec![
+            "-e",
+            "on run argv\n    display dialog (item 2 of argv)\nend run",
+            ARGV_HEAD,
+            "; quit",
+        ];
+        assert_eq!("osascript", got.get_program());
+        assert_eq!(want, got.get_args().collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_cmd_arg() {
         let mut got = Cmd::new("p");
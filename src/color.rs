@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value as Json;
+use std::fmt;
+use std::str::FromStr;
+
+/// A color of the 16-bit red, green and blue components, the RGB color of apple script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color(pub [u16; 3]);
+
+impl Color {
+    /// The color of the 8-bit components, 255 is 65535.
+    pub fn from_rgb(x: [u8; 3]) -> Color {
+        Color(x.map(|c| u16::from(c) * 257))
+    }
+    /// The 8-bit components, rounded.
+    pub fn rgb(&self) -> [u8; 3] {
+        self.0.map(|c| ((u32::from(c) + 128) / 257) as u8)
+    }
+    /// The color as #rrggbb.
+    pub fn hex(&self) -> String {
+        let [r, g, b] = self.rgb();
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+    /// The components as reals from 0 to 1, the RGB color of JavaScript for Automation.
+    pub fn reals(&self) -> [f64; 3] {
+        self.0.map(|c| f64::from(c) / 65535.0)
+    }
+    /// Read a list of three integers from 0 to 65535.
+    pub fn from_json(x: &Json) -> Option<Color> {
+        let xs = x.as_array()?;
+        if xs.len() != 3 {
+            return None;
+        }
+        let mut c = [0; 3];
+        for (c, x) in c.iter_mut().zip(xs) {
+            *c = u16::try_from(x.as_u64()?).ok()?;
+        }
+        Some(Color(c))
+    }
+    /// Parse #rrggbb, or rgb(r,g,b) and rgba(r,g,b,a) of the 8-bit components printed by zenity.
+    pub(crate) fn parse_css(x: &str) -> Result<Color> {
+        let x = x.trim();
+        let args = x
+            .strip_prefix("rgba(")
+            .or_else(|| x.strip_prefix("rgb("))
+            .and_then(|x| x.strip_suffix(')'));
+        let Some(args) = args else {
+            return x.parse();
+        };
+        let xs: Vec<_> = args.split(',').map(|x| x.trim().parse::<u8>()).collect();
+        match xs[..] {
+            [Ok(r), Ok(g), Ok(b)] | [Ok(r), Ok(g), Ok(b), _] => Ok(Color::from_rgb([r, g, b])),
+            _ => Err(anyhow!("not a color: {x}")),
+        }
+    }
+}
+
+/// Parse #RRGGBB, the number sign is optional.
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let x = s.strip_prefix('#').unwrap_or(s);
+        if x.len() != 6 || !x.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!("not a color: {s}, expected #RRGGBB"));
+        }
+        let c = |i: usize| u8::from_str_radix(&x[i..i + 2], 16);
+        Ok(Color::from_rgb([c(0)?, c(2)?, c(4)?]))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            Color([65535, 32896, 0]),
+            "#FF8000".parse::<Color>().unwrap()
+        );
+        assert_eq!(Color([0, 257, 65535]), "0001ff".parse::<Color>().unwrap());
+        assert!("#fff".parse::<Color>().is_err());
+        assert!("#gg0000".parse::<Color>().is_err());
+        assert!("#ff000é".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn test_rgb() {
+        let c = Color([65535, 32767, 128]);
+        assert_eq!([255, 127, 0], c.rgb());
+        assert_eq!("#ff7f00", c.hex());
+        let c: Color = "#12abef".parse().unwrap();
+        assert_eq!("#12abef", c.to_string());
+        assert_eq!([1.0, 0.0, 0.0], Color([65535, 0, 0]).reals());
+    }
+
+    #[test]
+    fn test_from_json() {
+        assert_eq!(
+            Some(Color([65535, 0, 1])),
+            Color::from_json(&json!([65535, 0, 1]))
+        );
+        assert_eq!(None, Color::from_json(&json!([65536, 0, 1])));
+        assert_eq!(None, Color::from_json(&json!([0, 1])));
+        assert_eq!(None, Color::from_json(&json!(false)));
+    }
+
+    #[test]
+    fn test_parse_css() {
+        let want = Color([65535, 0, 32896]);
+        assert_eq!(want, Color::parse_css("rgb(255,0,128)\n").unwrap());
+        assert_eq!(want, Color::parse_css("rgba(255, 0, 128, 0.5)").unwrap());
+        assert_eq!(want, Color::parse_css("#ff0080").unwrap());
+        assert!(Color::parse_css("rgb(256,0,0)").is_err());
+    }
+}
@@ -0,0 +1,950 @@
+use crate::backend::osascript::Osascript;
+use crate::backend::Backend;
+use crate::cli::{AlertType, Commands, Urgency};
+use crate::color::Color;
+use crate::response::Data;
+use anyhow::{anyhow, Result};
+use serde_json::Value as Json;
+use std::path::PathBuf;
+
+/// The result of [Notification::display].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotificationReply {
+    pub activation_type: Option<String>,
+    pub action: Option<String>,
+    pub reply: Option<String>,
+}
+
+/// Posts a notification using the Notification Center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    text: String,
+    title: Option<String>,
+    subtitle: Option<String>,
+    sound_name: Option<String>,
+    urgency: Option<Urgency>,
+    expire_timeout: Option<i32>,
+    actions: Vec<String>,
+    close_label: Option<String>,
+    reply: Option<String>,
+    timeout: Option<u32>,
+    group: Option<String>,
+    open: Option<String>,
+    execute: Option<String>,
+    activate: Option<String>,
+    app_icon: Option<String>,
+    osascript: Osascript,
+}
+
+impl Notification {
+    pub fn new<S: Into<String>>(text: S) -> Notification {
+        Notification {
+            text: text.into(),
+            title: None,
+            subtitle: None,
+            sound_name: None,
+            urgency: None,
+            expire_timeout: None,
+            actions: vec![],
+            close_label: None,
+            reply: None,
+            timeout: None,
+            group: None,
+            open: None,
+            execute: None,
+            activate: None,
+            app_icon: None,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn title<S: Into<String>>(mut self, x: S) -> Self {
+        self.title = Some(x.into());
+        self
+    }
+    pub fn subtitle<S: Into<String>>(mut self, x: S) -> Self {
+        self.subtitle = Some(x.into());
+        self
+    }
+    /// The base name of a sound installed in Library/Sounds.
+    pub fn sound_name<S: Into<String>>(mut self, x: S) -> Self {
+        self.sound_name = Some(x.into());
+        self
+    }
+    pub fn urgency(mut self, x: Urgency) -> Self {
+        self.urgency = Some(x);
+        self
+    }
+    /// Milliseconds until the notification expires, 0 never expires.
+    pub fn expire_timeout(mut self, ms: i32) -> Self {
+        self.expire_timeout = Some(ms);
+        self
+    }
+    /// Labels of the action buttons.
+    pub fn actions<I: IntoIterator<Item = S>, S: Into<String>>(mut self, xs: I) -> Self {
+        self.actions = xs.into_iter().map(Into::into).collect();
+        self
+    }
+    /// The label of the button closing the notification.
+    pub fn close_label<S: Into<String>>(mut self, x: S) -> Self {
+        self.close_label = Some(x.into());
+        self
+    }
+    /// Display a reply field with the placeholder, actions are ignored.
+    pub fn reply<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.reply = Some(placeholder.into());
+        self
+    }
+    /// Seconds until the notification is closed automatically.
+    pub fn timeout(mut self, seconds: u32) -> Self {
+        self.timeout = Some(seconds);
+        self
+    }
+    /// The notification replaces the previous notification of the group.
+    pub fn group<S: Into<String>>(mut self, x: S) -> Self {
+        self.group = Some(x.into());
+        self
+    }
+    /// The URL opened when the notification is clicked.
+    pub fn open<S: Into<String>>(mut self, x: S) -> Self {
+        self.open = Some(x.into());
+        self
+    }
+    /// The shell command run when the notification is clicked.
+    pub fn execute<S: Into<String>>(mut self, x: S) -> Self {
+        self.execute = Some(x.into());
+        self
+    }
+    /// The bundle identifier of the application activated when the notification is clicked.
+    pub fn activate<S: Into<String>>(mut self, x: S) -> Self {
+        self.activate = Some(x.into());
+        self
+    }
+    /// The URL of the image displayed as the application icon.
+    pub fn app_icon<S: Into<String>>(mut self, x: S) -> Self {
+        self.app_icon = Some(x.into());
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<NotificationReply> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<NotificationReply> {
+        match backend.run(&self.into())? {
+            Data::Notification {
+                activation_type,
+                action,
+                reply,
+            } => Ok(NotificationReply {
+                activation_type,
+                action,
+                reply,
+            }),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&Notification> for Commands {
+    fn from(from: &Notification) -> Self {
+        Commands::Notification {
+            text: from.text.clone(),
+            title: from.title.clone(),
+            subtitle: from.subtitle.clone(),
+            sound_name: from.sound_name.clone(),
+            urgency: from.urgency,
+            expire_timeout: from.expire_timeout,
+            actions: from.actions.clone(),
+            close_label: from.close_label.clone(),
+            reply: from.reply.clone(),
+            timeout: from.timeout,
+            group: from.group.clone(),
+            open: from.open.clone(),
+            execute: from.execute.clone(),
+            activate: from.activate.clone(),
+            app_icon: from.app_icon.clone(),
+            remove: None,
+        }
+    }
+}
+
+/// The result of [Alert::display].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertReply {
+    pub button: Option<String>,
+    pub gave_up: bool,
+    pub record: Json,
+}
+
+/// Displays a standardized alert containing a message, explanation, and from one to three buttons.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    text: String,
+    message: Option<String>,
+    alert_type: Option<AlertType>,
+    buttons: Vec<String>,
+    default_button: Option<String>,
+    cancel_button: Option<String>,
+    giving_up_after: Option<u8>,
+    osascript: Osascript,
+}
+
+impl Alert {
+    pub fn new<S: Into<String>>(text: S) -> Alert {
+        Alert {
+            text: text.into(),
+            message: None,
+            alert_type: None,
+            buttons: vec![],
+            default_button: None,
+            cancel_button: None,
+            giving_up_after: None,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn message<S: Into<String>>(mut self, x: S) -> Self {
+        self.message = Some(x.into());
+        self
+    }
+    pub fn alert_type(mut self, x: AlertType) -> Self {
+        self.alert_type = Some(x);
+        self
+    }
+    /// Up to three button names.
+    pub fn buttons<I: IntoIterator<Item = S>, S: Into<String>>(mut self, xs: I) -> Self {
+        self.buttons = xs.into_iter().map(Into::into).collect();
+        self
+    }
+    /// The name or number of the default button.
+    pub fn default_button<S: Into<String>>(mut self, x: S) -> Self {
+        self.default_button = Some(x.into());
+        self
+    }
+    /// The name or number of the cancel button.
+    pub fn cancel_button<S: Into<String>>(mut self, x: S) -> Self {
+        self.cancel_button = Some(x.into());
+        self
+    }
+    pub fn giving_up_after(mut self, seconds: u8) -> Self {
+        self.giving_up_after = Some(seconds);
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<AlertReply> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<AlertReply> {
+        match backend.run(&self.into())? {
+            Data::Alert {
+                record,
+                button,
+                gave_up,
+                ..
+            } => Ok(AlertReply {
+                button,
+                gave_up,
+                record,
+            }),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&Alert> for Commands {
+    fn from(from: &Alert) -> Self {
+        Commands::Alert {
+            text: from.text.clone(),
+            message: from.message.clone(),
+            alert_type: from.alert_type,
+            buttons: from.buttons.clone(),
+            default_button: from.default_button.clone(),
+            cancel_button: from.cancel_button.clone(),
+            giving_up_after: from.giving_up_after,
+        }
+    }
+}
+
+/// The result of [Dialog::display].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogReply {
+    pub text: Option<String>,
+    pub button: Option<String>,
+    pub gave_up: bool,
+    pub record: Json,
+}
+
+/// Displays a dialog containing a message, one to three buttons, and optionally an icon and a ﬁeld in which the user can enter text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialog {
+    text: String,
+    default_answer: Option<String>,
+    hidden_answer: bool,
+    buttons: Vec<String>,
+    default_button: Option<String>,
+    cancel_button: Option<String>,
+    title: Option<String>,
+    icon: Option<String>,
+    giving_up_after: Option<u8>,
+    osascript: Osascript,
+}
+
+impl Dialog {
+    pub fn new<S: Into<String>>(text: S) -> Dialog {
+        Dialog {
+            text: text.into(),
+            default_answer: None,
+            hidden_answer: false,
+            buttons: vec![],
+            default_button: None,
+            cancel_button: None,
+            title: None,
+            icon: None,
+            giving_up_after: None,
+            osascript: Osascript::default(),
+        }
+    }
+    /// The initial contents of an edit field, the field is present only if this is set.
+    pub fn default_answer<S: Into<String>>(mut self, x: S) -> Self {
+        self.default_answer = Some(x.into());
+        self
+    }
+    pub fn hidden_answer(mut self, x: bool) -> Self {
+        self.hidden_answer = x;
+        self
+    }
+    /// Up to three button names.
+    pub fn buttons<I: IntoIterator<Item = S>, S: Into<String>>(mut self, xs: I) -> Self {
+        self.buttons = xs.into_iter().map(Into::into).collect();
+        self
+    }
+    /// The name or number of the default button.
+    pub fn default_button<S: Into<String>>(mut self, x: S) -> Self {
+        self.default_button = Some(x.into());
+        self
+    }
+    /// The name or number of the cancel button.
+    pub fn cancel_button<S: Into<String>>(mut self, x: S) -> Self {
+        self.cancel_button = Some(x.into());
+        self
+    }
+    pub fn title<S: Into<String>>(mut self, x: S) -> Self {
+        self.title = Some(x.into());
+        self
+    }
+    /// stop, note, caution, a resource id or name, or a POSIX path of a .icns file.
+    pub fn icon<S: Into<String>>(mut self, x: S) -> Self {
+        self.icon = Some(x.into());
+        self
+    }
+    pub fn giving_up_after(mut self, seconds: u8) -> Self {
+        self.giving_up_after = Some(seconds);
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<DialogReply> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<DialogReply> {
+        match backend.run(&self.into())? {
+            Data::Dialog {
+                record,
+                text,
+                button,
+                gave_up,
+                ..
+            } => Ok(DialogReply {
+                text,
+                button,
+                gave_up,
+                record,
+            }),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&Dialog> for Commands {
+    fn from(from: &Dialog) -> Self {
+        Commands::Dialog {
+            text: from.text.clone(),
+            default_answer: from.default_answer.clone(),
+            hidden_answer: from.hidden_answer,
+            buttons: from.buttons.clone(),
+            default_button: from.default_button.clone(),
+            cancel_button: from.cancel_button.clone(),
+            title: from.title.clone(),
+            icon: from.icon.clone(),
+            giving_up_after: from.giving_up_after,
+        }
+    }
+}
+
+/// The result of [ChooseFromList::display].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFromListReply {
+    pub items: Vec<String>,
+    pub cancelled: bool,
+}
+
+/// Allows the user to choose items from a list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFromList {
+    items: Vec<String>,
+    prompt: Option<String>,
+    title: Option<String>,
+    default_items: Vec<String>,
+    ok_button_name: Option<String>,
+    cancel_button_name: Option<String>,
+    multiple_selections_allowed: bool,
+    empty_selection_allowed: bool,
+    osascript: Osascript,
+}
+
+impl ChooseFromList {
+    pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(items: I) -> ChooseFromList {
+        ChooseFromList {
+            items: items.into_iter().map(Into::into).collect(),
+            prompt: None,
+            title: None,
+            default_items: vec![],
+            ok_button_name: None,
+            cancel_button_name: None,
+            multiple_selections_allowed: false,
+            empty_selection_allowed: false,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn prompt<S: Into<String>>(mut self, x: S) -> Self {
+        self.prompt = Some(x.into());
+        self
+    }
+    pub fn title<S: Into<String>>(mut self, x: S) -> Self {
+        self.title = Some(x.into());
+        self
+    }
+    /// The items initially selected.
+    pub fn default_items<I: IntoIterator<Item = S>, S: Into<String>>(mut self, xs: I) -> Self {
+        self.default_items = xs.into_iter().map(Into::into).collect();
+        self
+    }
+    pub fn ok_button_name<S: Into<String>>(mut self, x: S) -> Self {
+        self.ok_button_name = Some(x.into());
+        self
+    }
+    pub fn cancel_button_name<S: Into<String>>(mut self, x: S) -> Self {
+        self.cancel_button_name = Some(x.into());
+        self
+    }
+    pub fn multiple_selections_allowed(mut self, x: bool) -> Self {
+        self.multiple_selections_allowed = x;
+        self
+    }
+    pub fn empty_selection_allowed(mut self, x: bool) -> Self {
+        self.empty_selection_allowed = x;
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<ChooseFromListReply> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<ChooseFromListReply> {
+        match backend.run(&self.into())? {
+            Data::ChooseFromList { items, cancelled } => {
+                Ok(ChooseFromListReply { items, cancelled })
+            }
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&ChooseFromList> for Commands {
+    fn from(from: &ChooseFromList) -> Self {
+        Commands::ChooseFromList {
+            items: from.items.clone(),
+            prompt: from.prompt.clone(),
+            title: from.title.clone(),
+            default_items: from.default_items.clone(),
+            ok_button_name: from.ok_button_name.clone(),
+            cancel_button_name: from.cancel_button_name.clone(),
+            multiple_selections_allowed: from.multiple_selections_allowed,
+            empty_selection_allowed: from.empty_selection_allowed,
+        }
+    }
+}
+
+/// Allows the user to choose files, the result is their POSIX paths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFile {
+    prompt: Option<String>,
+    of_type: Vec<String>,
+    default_location: Option<PathBuf>,
+    invisibles: bool,
+    multiple_selections_allowed: bool,
+    showing_package_contents: bool,
+    osascript: Osascript,
+}
+
+impl Default for ChooseFile {
+    fn default() -> Self {
+        ChooseFile::new()
+    }
+}
+
+impl ChooseFile {
+    pub fn new() -> ChooseFile {
+        ChooseFile {
+            prompt: None,
+            of_type: vec![],
+            default_location: None,
+            invisibles: false,
+            multiple_selections_allowed: false,
+            showing_package_contents: false,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn prompt<S: Into<String>>(mut self, x: S) -> Self {
+        self.prompt = Some(x.into());
+        self
+    }
+    /// Uniform type identifiers of the files that can be chosen.
+    pub fn of_type<I: IntoIterator<Item = S>, S: Into<String>>(mut self, xs: I) -> Self {
+        self.of_type = xs.into_iter().map(Into::into).collect();
+        self
+    }
+    /// The folder to begin browsing in.
+    pub fn default_location<P: Into<PathBuf>>(mut self, x: P) -> Self {
+        self.default_location = Some(x.into());
+        self
+    }
+    pub fn invisibles(mut self, x: bool) -> Self {
+        self.invisibles = x;
+        self
+    }
+    pub fn multiple_selections_allowed(mut self, x: bool) -> Self {
+        self.multiple_selections_allowed = x;
+        self
+    }
+    pub fn showing_package_contents(mut self, x: bool) -> Self {
+        self.showing_package_contents = x;
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<Vec<String>> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<Vec<String>> {
+        match backend.run(&self.into())? {
+            Data::ChooseFile { paths } => Ok(paths),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&ChooseFile> for Commands {
+    fn from(from: &ChooseFile) -> Self {
+        Commands::ChooseFile {
+            prompt: from.prompt.clone(),
+            of_type: from.of_type.clone(),
+            default_location: from.default_location.clone(),
+            invisibles: from.invisibles,
+            multiple_selections_allowed: from.multiple_selections_allowed,
+            showing_package_contents: from.showing_package_contents,
+        }
+    }
+}
+
+/// Allows the user to choose folders, the result is their POSIX paths ending with a slash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFolder {
+    prompt: Option<String>,
+    default_location: Option<PathBuf>,
+    invisibles: bool,
+    multiple_selections_allowed: bool,
+    showing_package_contents: bool,
+    osascript: Osascript,
+}
+
+impl Default for ChooseFolder {
+    fn default() -> Self {
+        ChooseFolder::new()
+    }
+}
+
+impl ChooseFolder {
+    pub fn new() -> ChooseFolder {
+        ChooseFolder {
+            prompt: None,
+            default_location: None,
+            invisibles: false,
+            multiple_selections_allowed: false,
+            showing_package_contents: false,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn prompt<S: Into<String>>(mut self, x: S) -> Self {
+        self.prompt = Some(x.into());
+        self
+    }
+    /// The folder to begin browsing in.
+    pub fn default_location<P: Into<PathBuf>>(mut self, x: P) -> Self {
+        self.default_location = Some(x.into());
+        self
+    }
+    pub fn invisibles(mut self, x: bool) -> Self {
+        self.invisibles = x;
+        self
+    }
+    pub fn multiple_selections_allowed(mut self, x: bool) -> Self {
+        self.multiple_selections_allowed = x;
+        self
+    }
+    pub fn showing_package_contents(mut self, x: bool) -> Self {
+        self.showing_package_contents = x;
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<Vec<String>> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<Vec<String>> {
+        match backend.run(&self.into())? {
+            Data::ChooseFolder { paths } => Ok(paths),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&ChooseFolder> for Commands {
+    fn from(from: &ChooseFolder) -> Self {
+        Commands::ChooseFolder {
+            prompt: from.prompt.clone(),
+            default_location: from.default_location.clone(),
+            invisibles: from.invisibles,
+            multiple_selections_allowed: from.multiple_selections_allowed,
+            showing_package_contents: from.showing_package_contents,
+        }
+    }
+}
+
+/// The result of [ChooseFileName::display].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFileNameReply {
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Allows the user to specify a new file name and location, the file is not created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseFileName {
+    prompt: Option<String>,
+    default_name: Option<String>,
+    default_location: Option<PathBuf>,
+    osascript: Osascript,
+}
+
+impl Default for ChooseFileName {
+    fn default() -> Self {
+        ChooseFileName::new()
+    }
+}
+
+impl ChooseFileName {
+    pub fn new() -> ChooseFileName {
+        ChooseFileName {
+            prompt: None,
+            default_name: None,
+            default_location: None,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn prompt<S: Into<String>>(mut self, x: S) -> Self {
+        self.prompt = Some(x.into());
+        self
+    }
+    pub fn default_name<S: Into<String>>(mut self, x: S) -> Self {
+        self.default_name = Some(x.into());
+        self
+    }
+    /// The folder to begin browsing in.
+    pub fn default_location<P: Into<PathBuf>>(mut self, x: P) -> Self {
+        self.default_location = Some(x.into());
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<ChooseFileNameReply> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<ChooseFileNameReply> {
+        match backend.run(&self.into())? {
+            Data::ChooseFileName { path, exists } => Ok(ChooseFileNameReply { path, exists }),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&ChooseFileName> for Commands {
+    fn from(from: &ChooseFileName) -> Self {
+        Commands::ChooseFileName {
+            prompt: from.prompt.clone(),
+            default_name: from.default_name.clone(),
+            default_location: from.default_location.clone(),
+        }
+    }
+}
+
+/// Allows the user to choose a color from a color picker dialog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChooseColor {
+    default: Option<Color>,
+    osascript: Osascript,
+}
+
+impl Default for ChooseColor {
+    fn default() -> Self {
+        ChooseColor::new()
+    }
+}
+
+impl ChooseColor {
+    pub fn new() -> ChooseColor {
+        ChooseColor {
+            default: None,
+            osascript: Osascript::default(),
+        }
+    }
+    /// The color initially selected.
+    pub fn default_color(mut self, x: Color) -> Self {
+        self.default = Some(x);
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<Color> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<Color> {
+        match backend.run(&self.into())? {
+            Data::Color { components, .. } => Ok(Color(components)),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&ChooseColor> for Commands {
+    fn from(from: &ChooseColor) -> Self {
+        Commands::ChooseColor {
+            default: from.default,
+        }
+    }
+}
+
+/// Speaks the text, or saves the speech to an audio file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Say {
+    text: String,
+    voice: Option<String>,
+    rate: Option<f64>,
+    pitch: Option<f64>,
+    modulation: Option<f64>,
+    no_wait: bool,
+    save_to: Option<PathBuf>,
+    osascript: Osascript,
+}
+
+impl Say {
+    pub fn new<S: Into<String>>(text: S) -> Say {
+        Say {
+            text: text.into(),
+            voice: None,
+            rate: None,
+            pitch: None,
+            modulation: None,
+            no_wait: false,
+            save_to: None,
+            osascript: Osascript::default(),
+        }
+    }
+    pub fn voice<S: Into<String>>(mut self, x: S) -> Self {
+        self.voice = Some(x.into());
+        self
+    }
+    /// Words per minute.
+    pub fn rate(mut self, x: f64) -> Self {
+        self.rate = Some(x);
+        self
+    }
+    pub fn pitch(mut self, x: f64) -> Self {
+        self.pitch = Some(x);
+        self
+    }
+    pub fn modulation(mut self, x: f64) -> Self {
+        self.modulation = Some(x);
+        self
+    }
+    /// Return without waiting until the speech is completed.
+    pub fn no_wait(mut self, x: bool) -> Self {
+        self.no_wait = x;
+        self
+    }
+    /// Save the speech to the AIFF file instead of speaking.
+    pub fn save_to<P: Into<PathBuf>>(mut self, x: P) -> Self {
+        self.save_to = Some(x.into());
+        self
+    }
+    pub fn osascript(mut self, x: Osascript) -> Self {
+        self.osascript = x;
+        self
+    }
+    pub fn display(&self) -> Result<()> {
+        self.display_with(&self.osascript)
+    }
+    pub fn display_with(&self, backend: &dyn Backend) -> Result<()> {
+        match backend.run(&self.into())? {
+            Data::Say { .. } => Ok(()),
+            x => Err(anyhow!("unexpected result: {x:?}")),
+        }
+    }
+}
+
+impl From<&Say> for Commands {
+    fn from(from: &Say) -> Self {
+        Commands::Say {
+            text: from.text.clone(),
+            voice: from.voice.clone(),
+            rate: from.rate,
+            pitch: from.pitch,
+            modulation: from.modulation,
+            no_wait: from.no_wait,
+            save_to: from.save_to.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dialog_cmd() {
+        let d = Dialog::new("t")
+            .buttons(["A", "B"])
+            .default_button("2")
+            .title("x");
+        let got = Osascript::default().cmd(&(&d).into()).unwrap();
+        let got = std::process::Command::from(got);
+        let want = vec![
+            "-s",
+            "s",
+            "-e",
+            "on run argv\n    display dialog (item 2 of argv) buttons {(item 3 of argv), (item 4 of argv)} default button 2 with title (item 5 of argv)\nend run",
+            "scptdisplay",
+            "t",
+            "A",
+            "B",
+            "x",
+        ];
+        assert_eq!(want, got.get_args().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_dialog_display() {
+        // echo prints its arguments, which are not a record
+        let got = Dialog::new("t")
+            .osascript(Osascript::default().program("echo"))
+            .display();
+        assert!(got.is_err());
+    }
+
+    #[test]
+    fn test_notification_display() {
+        let got = Notification::new("t")
+            .title("x")
+            .osascript(Osascript::default().program("true"))
+            .display();
+        assert!(got.is_ok());
+    }
+
+    #[test]
+    fn test_choose_from_list_cmd() {
+        let x = ChooseFromList::new(["a", "b"])
+            .default_items(["b"])
+            .multiple_selections_allowed(true);
+        let got = Osascript::default().inline(true).cmd(&(&x).into()).unwrap();
+        let got = std::process::Command::from(got);
+        let want = vec![
+            "-s",
+            "s",
+            "-e",
+            r#"choose from list {"a", "b"} default items {"b"} multiple selections allowed true"#,
+        ];
+        assert_eq!(want, got.get_args().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_choose_color_cmd() {
+        let x = ChooseColor::new().default_color("#ff8000".parse().unwrap());
+        let got = Osascript::default().cmd(&(&x).into()).unwrap();
+        let got = std::process::Command::from(got);
+        let want = vec![
+            "-s",
+            "s",
+            "-e",
+            "on run argv\n    choose color default color {65535, 32896, 0}\nend run",
+            "scptdisplay",
+        ];
+        assert_eq!(want, got.get_args().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_say_display() {
+        let got = Say::new("t")
+            .voice("Samantha")
+            .osascript(Osascript::default().program("true"))
+            .display();
+        assert!(got.is_ok());
+    }
+
+    #[test]
+    fn test_choose_folder_cmd() {
+        let x = ChooseFolder::new()
+            .prompt("p")
+            .default_location("/tmp")
+            .multiple_selections_allowed(true);
+        let got = Osascript::default().cmd(&(&x).into()).unwrap();
+        let got = std::process::Command::from(got);
+        let want = format!(
+            "on run argv\n    posixPaths((choose folder with prompt (item 2 of argv) default location ((POSIX file (item 3 of argv)) as alias) multiple selections allowed true))\nend run\n\n{}",
+            crate::script::Handler::PosixPaths.definition()
+        );
+        let got: Vec<_> = got
+            .get_args()
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            vec!["-s", "s", "-e", &want, "scptdisplay", "p", "/tmp"],
+            got
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An error of the script, decoded from stderr of osascript such as
+/// `0:20: execution error: User canceled. (-128)`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+pub struct ScriptError {
+    /// The error message.
+    pub message: String,
+    /// The apple script error number.
+    pub number: Option<i32>,
+    /// The range of the script where the error occurred.
+    pub range: Option<Range>,
+    pub category: Category,
+}
+
+/// A range of the script.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
+pub struct Range {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A category of the error.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// The user pressed the cancel button (-128).
+    UserCancelled,
+    /// Not authorized to send apple events (-1743).
+    NotAuthorized,
+    /// Apple event timed out (-1712).
+    Timeout,
+    SyntaxError,
+    Unknown,
+}
+
+impl Category {
+    fn new(number: Option<i32>, is_syntax: bool) -> Category {
+        match number {
+            Some(-128) => Category::UserCancelled,
+            Some(-1743) => Category::NotAuthorized,
+            Some(-1712) => Category::Timeout,
+            Some(-2741 | -2740) => Category::SyntaxError,
+            _ if is_syntax => Category::SyntaxError,
+            _ => Category::Unknown,
+        }
+    }
+}
+
+impl ScriptError {
+    pub fn new<S: Into<String>>(message: S, number: Option<i32>) -> ScriptError {
+        ScriptError {
+            message: message.into(),
+            number,
+            range: None,
+            category: Category::new(number, false),
+        }
+    }
+
+    /// An error without any detail.
+    pub fn unknown<S: Into<String>>(message: S) -> ScriptError {
+        ScriptError::new(message, None)
+    }
+
+    /// Decode stderr of osascript.
+    pub fn parse(stderr: &str) -> ScriptError {
+        let mut x = stderr.trim();
+
+        let mut range = None;
+        let mut xs = x.splitn(3, ':');
+        if let (Some(start), Some(end), Some(rest)) = (xs.next(), xs.next(), xs.next()) {
+            if let (Ok(start), Ok(end)) = (start.parse(), end.parse()) {
+                range = Some(Range { start, end });
+                x = rest.trim_start();
+            }
+        }
+
+        let mut is_syntax = false;
+        if let Some(rest) = x.strip_prefix("execution error:") {
+            x = rest.trim_start();
+        } else if let Some(rest) = x.strip_prefix("syntax error:") {
+            x = rest.trim_start();
+            is_syntax = true;
+        }
+        // JavaScript for Automation prefixes the class of the error
+        while let Some(rest) = x.strip_prefix("Error:") {
+            x = rest.trim_start();
+        }
+
+        let mut number = None;
+        if let Some((rest, n)) = x.strip_suffix(')').and_then(|x| x.rsplit_once(" (")) {
+            if let Ok(n) = n.parse() {
+                number = Some(n);
+                x = rest;
+            }
+        }
+
+        ScriptError {
+            message: x.into(),
+            number,
+            range,
+            category: Category::new(number, is_syntax),
+        }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.number {
+            Some(n) => write!(f, "{} ({n})", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// A script error with the exit status of the invoked process.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProcessError {
+    pub code: Option<i32>,
+    pub error: ScriptError,
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for ProcessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_parse {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = ScriptError::parse($input);
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_parse!(
+        test_parse_user_cancelled,
+        "0:20: execution error: User canceled. (-128)\n",
+        ScriptError {
+            message: "User canceled.".into(),
+            number: Some(-128),
+            range: Some(Range { start: 0, end: 20 }),
+            category: Category::UserCancelled,
+        }
+    );
+    test_parse!(
+        test_parse_not_authorized,
+        "12:40: execution error: Not authorized to send Apple events to Finder. (-1743)",
+        ScriptError {
+            message: "Not authorized to send Apple events to Finder.".into(),
+            number: Some(-1743),
+            range: Some(Range { start: 12, end: 40 }),
+            category: Category::NotAuthorized,
+        }
+    );
+    test_parse!(
+        test_parse_timeout,
+        "execution error: AppleEvent timed out. (-1712)",
+        ScriptError {
+            message: "AppleEvent timed out.".into(),
+            number: Some(-1712),
+            range: None,
+            category: Category::Timeout,
+        }
+    );
+    test_parse!(
+        test_parse_syntax_error,
+        "31:37: syntax error: Expected end of line but found identifier. (-2741)",
+        ScriptError {
+            message: "Expected end of line but found identifier.".into(),
+            number: Some(-2741),
+            range: Some(Range { start: 31, end: 37 }),
+            category: Category::SyntaxError,
+        }
+    );
+    test_parse!(
+        test_parse_javascript,
+        "execution error: Error: Error: User canceled. (-128)",
+        ScriptError {
+            message: "User canceled.".into(),
+            number: Some(-128),
+            range: None,
+            category: Category::UserCancelled,
+        }
+    );
+    test_parse!(
+        test_parse_unknown,
+        "osascript: no such file",
+        ScriptError::unknown("osascript: no such file")
+    );
+    test_parse!(
+        test_parse_other_number,
+        "0:5: execution error: The variable x is not defined. (-2753)",
+        ScriptError {
+            message: "The variable x is not defined.".into(),
+            number: Some(-2753),
+            range: Some(Range { start: 0, end: 5 }),
+            category: Category::Unknown,
+        }
+    );
+}
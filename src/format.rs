@@ -0,0 +1,358 @@
+use crate::response::Response;
+use clap::ValueEnum;
+use serde_json::Value as Json;
+
+/// Format of the output.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    /// A json.
+    Json,
+    /// Lines of name and value separated by a tab, or the value of the field, a list is printed one item per line.
+    Text,
+    /// Lines of shell variable assignments such as SCPT_BUTTON='OK', safe to eval.
+    Shell,
+    /// A json per line, a list is printed one item per line.
+    Ndjson,
+}
+
+/// A field of the data to print.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Field {
+    Text,
+    Button,
+    #[value(name = "gave_up")]
+    GaveUp,
+    Raw,
+    Action,
+    Reply,
+    #[value(name = "activation_type")]
+    ActivationType,
+    Items,
+    Cancelled,
+    Paths,
+    Path,
+    Exists,
+    Hex,
+    Rgb,
+    Components,
+    SavedTo,
+    Voices,
+}
+
+impl Field {
+    fn name(&self) -> &'static str {
+        match self {
+            Field::Text => "text",
+            Field::Button => "button",
+            Field::GaveUp => "gave_up",
+            Field::Raw => "raw",
+            Field::Action => "action",
+            Field::Reply => "reply",
+            Field::ActivationType => "activation_type",
+            Field::Items => "items",
+            Field::Cancelled => "cancelled",
+            Field::Paths => "paths",
+            Field::Path => "path",
+            Field::Exists => "exists",
+            Field::Hex => "hex",
+            Field::Rgb => "rgb",
+            Field::Components => "components",
+            Field::SavedTo => "saved_to",
+            Field::Voices => "voices",
+        }
+    }
+}
+
+/// The fields of the data, kind comes first.
+fn data_fields(data: &Json) -> Vec<(String, Json)> {
+    let mut xs = vec![];
+    if let Json::Object(m) = data {
+        xs.push(("kind".to_string(), m["kind"].clone()));
+        xs.extend(
+            m.iter()
+                .filter(|(k, _)| *k != "kind")
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+    }
+    xs
+}
+
+/// The fields of the response.
+fn fields(response: &Response) -> Vec<(String, Json)> {
+    let err = response.error();
+    let mut xs = vec![
+        ("version".to_string(), response.version().into()),
+        ("result".to_string(), response.result().to_string().into()),
+        ("code".to_string(), response.code().into()),
+        (
+            "error_message".to_string(),
+            err.map(|x| x.message.clone()).into(),
+        ),
+        (
+            "error_number".to_string(),
+            err.and_then(|x| x.number).into(),
+        ),
+        (
+            "error_category".to_string(),
+            err.map(|x| serde_json::to_value(x.category).unwrap())
+                .unwrap_or_default(),
+        ),
+    ];
+    let data = serde_json::to_value(response.data()).unwrap();
+    let data = data_fields(&data);
+    if data.is_empty() {
+        xs.push(("kind".to_string(), Json::Null));
+    }
+    xs.extend(data);
+    // e.g. osascript=error,tui=ok, only if more than one backend ran
+    if response.attempts().len() < 2 {
+        return xs;
+    }
+    let attempts: Vec<String> = response
+        .attempts()
+        .iter()
+        .map(|x| format!("{}={}", x.backend, x.result))
+        .collect();
+    xs.push(("attempts".to_string(), attempts.join(",").into()));
+    xs
+}
+
+/// Plain text of the value: strings are not quoted and null is empty.
+fn plain(x: &Json) -> String {
+    match x {
+        Json::Null => "".into(),
+        Json::String(x) => x.clone(),
+        x => x.to_string(),
+    }
+}
+
+/// Quote the value for POSIX shells.
+///
+/// NUL characters are removed because shell variables cannot hold them.
+pub fn shell_quote(x: &str) -> String {
+    format!("'{}'", x.replace('\0', "").replace('\'', r"'\''"))
+}
+
+fn shell_assign(name: &str, x: &Json) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("SCPT_{name}={}", shell_quote(&plain(x)))
+}
+
+fn ndjson(x: &Json) -> String {
+    match x {
+        Json::Array(xs) => xs.iter().map(|x| format!("{x}\n")).collect(),
+        x => format!("{x}\n"),
+    }
+}
+
+/// Render the response or the field of its data.
+pub fn render(response: &Response, print: Option<Field>, format: Format) -> String {
+    let Some(field) = print else {
+        return match format {
+            // keep the order of the fields
+            Format::Json | Format::Ndjson => {
+                format!("{}\n", serde_json::to_string(response).unwrap())
+            }
+            Format::Text => fields(response)
+                .iter()
+                .map(|(k, v)| format!("{k}\t{}\n", plain(v)))
+                .collect(),
+            Format::Shell => fields(response)
+                .iter()
+                .map(|(k, v)| format!("{}\n", shell_assign(k, v)))
+                .collect(),
+        };
+    };
+    let data = serde_json::to_value(response.data()).unwrap();
+    let x = data_fields(&data)
+        .into_iter()
+        .find(|(k, _)| k == field.name())
+        .map(|(_, v)| v)
+        .unwrap_or(Json::Null);
+    match format {
+        Format::Json => format!("{x}\n"),
+        Format::Ndjson => ndjson(&x),
+        Format::Text => match &x {
+            Json::Array(xs) => xs.iter().map(|x| format!("{}\n", plain(x))).collect(),
+            x => format!("{}\n", plain(x)),
+        },
+        Format::Shell => format!("{}\n", shell_assign(field.name(), &x)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::{Attempt, Data};
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    fn dialog() -> Response {
+        Data::Dialog {
+            raw: "raw".into(),
+            record: json!({"button returned": "OK"}),
+            text: Some("it's $HOME\n`id`".into()),
+            button: Some("OK".into()),
+            gave_up: false,
+        }
+        .into()
+    }
+
+    macro_rules! test_shell_quote {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                assert_eq!($want, shell_quote($input));
+            }
+        };
+    }
+
+    test_shell_quote!(test_shell_quote_empty, "", "''");
+    test_shell_quote!(test_shell_quote_plain, "a b", "'a b'");
+    test_shell_quote!(test_shell_quote_quote, "it's", r"'it'\''s'");
+    test_shell_quote!(test_shell_quote_special, "$(id)\n`x`\\", "'$(id)\n`x`\\'");
+    test_shell_quote!(test_shell_quote_nul, "a\0b", "'ab'");
+
+    macro_rules! test_render {
+        ($name:ident, $response:expr, $print:expr, $format:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = render(&$response, $print, $format);
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_render!(
+        test_render_json_text,
+        dialog(),
+        Some(Field::Text),
+        Format::Json,
+        "\"it's $HOME\\n`id`\"\n"
+    );
+    test_render!(
+        test_render_text_button,
+        dialog(),
+        Some(Field::Button),
+        Format::Text,
+        "OK\n"
+    );
+    test_render!(
+        test_render_text_gave_up,
+        dialog(),
+        Some(Field::GaveUp),
+        Format::Text,
+        "false\n"
+    );
+    test_render!(
+        test_render_shell_text,
+        dialog(),
+        Some(Field::Text),
+        Format::Shell,
+        "SCPT_TEXT='it'\\''s $HOME\n`id`'\n"
+    );
+    test_render!(
+        test_render_ndjson_raw,
+        dialog(),
+        Some(Field::Raw),
+        Format::Ndjson,
+        "\"raw\"\n"
+    );
+    test_render!(
+        test_render_text_items,
+        Response::from(Data::ChooseFromList {
+            items: vec!["a b".into(), "c".into()],
+            cancelled: false
+        }),
+        Some(Field::Items),
+        Format::Text,
+        "a b\nc\n"
+    );
+    test_render!(
+        test_render_text_missing,
+        Response::from(anyhow!("failed")),
+        Some(Field::Button),
+        Format::Text,
+        "\n"
+    );
+    test_render!(
+        test_render_text_attempts,
+        Response::from(anyhow!("failed")).with_attempts(vec![
+            Attempt::new("osascript", &Err(anyhow!("failed"))),
+            Attempt::new("stderr", &Err(anyhow!("failed"))),
+        ]),
+        None,
+        Format::Text,
+        [
+            "version\t2",
+            "result\terror",
+            "code\t",
+            "error_message\tfailed",
+            "error_number\t",
+            "error_category\tunknown",
+            "kind\t",
+            "attempts\tosascript=error,stderr=error",
+            "",
+        ]
+        .join("\n")
+    );
+    test_render!(
+        test_render_json,
+        Response::from(Data::Notification {
+            activation_type: None,
+            action: None,
+            reply: None
+        }),
+        None,
+        Format::Json,
+        "{\"version\":2,\"result\":\"ok\",\"code\":0,\"error\":null,\"data\":{\"kind\":\"notification\",\"activation_type\":null,\"action\":null,\"reply\":null},\"attempts\":[]}\n"
+    );
+    test_render!(
+        test_render_shell,
+        dialog(),
+        None,
+        Format::Shell,
+        [
+            "SCPT_VERSION='2'",
+            "SCPT_RESULT='ok'",
+            "SCPT_CODE='0'",
+            "SCPT_ERROR_MESSAGE=''",
+            "SCPT_ERROR_NUMBER=''",
+            "SCPT_ERROR_CATEGORY=''",
+            "SCPT_KIND='dialog'",
+            "SCPT_BUTTON='OK'",
+            "SCPT_GAVE_UP='false'",
+            "SCPT_RAW='raw'",
+            "SCPT_RECORD='{\"button returned\":\"OK\"}'",
+            "SCPT_TEXT='it'\\''s $HOME\n`id`'",
+            "",
+        ]
+        .join("\n")
+    );
+    test_render!(
+        test_render_text_error,
+        Response::from(anyhow!("failed")),
+        None,
+        Format::Text,
+        [
+            "version\t2",
+            "result\terror",
+            "code\t",
+            "error_message\tfailed",
+            "error_number\t",
+            "error_category\tunknown",
+            "kind\t",
+            "",
+        ]
+        .join("\n")
+    );
+}
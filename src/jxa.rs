@@ -0,0 +1,233 @@
+use anyhow::Result;
+use serde_json::{Map, Value as Json};
+
+/// A JavaScript for Automation program.
+///
+/// The first argument is a json request:
+///   command(string): a method of the standard additions such as displayDialog.
+///   text(string, list of string or null): the direct parameter, null if the command has none.
+///   options(map): the optional parameters.
+///   paths(list of string): keys of the options whose values are POSIX paths.
+///   posixPaths(bool): if true, the result is a path or a list of paths, which is converted into a list of POSIX paths.
+///   rgbColor(bool): if true, the result is a list of reals from 0 to 1, which is converted into integers from 0 to 65535 as apple script.
+///
+/// The program prints the result as json.
+pub const PROGRAM: &str = r#"function run(argv) {
+    var app = Application.currentApplication();
+    app.includeStandardAdditions = true;
+    var request = JSON.parse(argv[0]);
+    var options = request.options;
+    request.paths.forEach(function (k) {
+        if (k in options) {
+            options[k] = Path(options[k]);
+        }
+    });
+    var result =
+        request.text === null
+            ? app[request.command](options)
+            : app[request.command](request.text, options);
+    if (request.posixPaths) {
+        result = [].concat(result).map(function (x) {
+            return x.toString();
+        });
+    }
+    if (request.rgbColor) {
+        result = result.map(function (x) {
+            return Math.round(x * 65535);
+        });
+    }
+    return JSON.stringify(result === undefined ? null : result);
+}"#;
+
+/// A request to the program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Request {
+    command: &'static str,
+    text: Json,
+    options: Map<String, Json>,
+    paths: Vec<&'static str>,
+    posix_paths: bool,
+    rgb_color: bool,
+}
+
+impl Request {
+    pub fn new<S: Into<String>>(command: &'static str, text: S) -> Request {
+        Request {
+            command,
+            text: Json::String(text.into()),
+            options: Map::new(),
+            paths: vec![],
+            posix_paths: false,
+            rgb_color: false,
+        }
+    }
+    /// A request whose direct parameter is a list of text.
+    pub fn list<S: AsRef<str>>(command: &'static str, xs: &[S]) -> Request {
+        Request {
+            command,
+            text: xs.iter().map(|x| x.as_ref()).collect(),
+            options: Map::new(),
+            paths: vec![],
+            posix_paths: false,
+            rgb_color: false,
+        }
+    }
+    /// A request without the direct parameter.
+    pub fn without_text(command: &'static str) -> Request {
+        Request {
+            command,
+            text: Json::Null,
+            options: Map::new(),
+            paths: vec![],
+            posix_paths: false,
+            rgb_color: false,
+        }
+    }
+    /// Convert the result into a list of POSIX paths.
+    pub fn posix_paths(mut self) -> Self {
+        self.posix_paths = true;
+        self
+    }
+    /// Convert the result of reals into the 16-bit components of apple script.
+    pub fn rgb_color(mut self) -> Self {
+        self.rgb_color = true;
+        self
+    }
+    /// Add an option if value is not None.
+    pub fn option<T: Into<Json>>(mut self, key: &'static str, value: Option<T>) -> Self {
+        if let Some(x) = value {
+            self.options.insert(key.into(), x.into());
+        }
+        self
+    }
+    /// Add an option that is a POSIX path if value is not None.
+    pub fn path<S: Into<String>>(mut self, key: &'static str, value: Option<S>) -> Self {
+        if let Some(x) = value {
+            self.options.insert(key.into(), Json::String(x.into()));
+            self.paths.push(key);
+        }
+        self
+    }
+}
+
+impl From<Request> for String {
+    fn from(from: Request) -> Self {
+        serde_json::json!({
+            "command": from.command,
+            "text": from.text,
+            "options": from.options,
+            "paths": from.paths,
+            "posixPaths": from.posix_paths,
+            "rgbColor": from.rgb_color,
+        })
+        .to_string()
+    }
+}
+
+/// Convert the key of the result into the apple script term, e.g. buttonReturned into button returned.
+fn term(x: &str) -> String {
+    let mut s = String::new();
+    for c in x.chars() {
+        if c.is_ascii_uppercase() {
+            s.push(' ');
+            s.push(c.to_ascii_lowercase());
+        } else {
+            s.push(c);
+        }
+    }
+    s
+}
+
+/// Parse the result printed by the program.
+///
+/// Keys of the record are converted into the apple script terms,
+/// so that the record is the same as the one of apple script.
+pub fn parse(x: &str) -> Result<Json> {
+    let v: Json = serde_json::from_str(x)?;
+    Ok(match v {
+        Json::Object(xs) => Json::Object(xs.into_iter().map(|(k, v)| (term(&k), v)).collect()),
+        Json::Null => Json::Object(Map::new()),
+        v => v,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request() {
+        let got: String = Request::new("displayDialog", "a\"b")
+            .option("buttons", Some(vec!["OK", "-x"]))
+            .option("withTitle", None::<String>)
+            .option("givingUpAfter", Some(3))
+            .path("withIcon", Some("/tmp/x.icns"))
+            .into();
+        let got: Json = serde_json::from_str(&got).unwrap();
+        let want = json!({
+            "command": "displayDialog",
+            "text": "a\"b",
+            "options": {
+                "buttons": ["OK", "-x"],
+                "givingUpAfter": 3,
+                "withIcon": "/tmp/x.icns",
+            },
+            "paths": ["withIcon"],
+            "posixPaths": false,
+            "rgbColor": false,
+        });
+        assert_eq!(want, got);
+    }
+
+    macro_rules! test_parse {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = parse($input).unwrap();
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_parse!(test_parse_null, "null\n", json!({}));
+    test_parse!(
+        test_parse_dialog,
+        r#"{"buttonReturned":"a, b: c","textReturned":"x\"y","gaveUp":false}"#,
+        json!({"button returned": "a, b: c", "text returned": "x\"y", "gave up": false})
+    );
+    test_parse!(test_parse_list, r#"["a"]"#, json!(["a"]));
+    test_parse!(test_parse_false, "false\n", json!(false));
+
+    #[test]
+    fn test_request_list() {
+        let got: String = Request::list("chooseFromList", &["a", "b"])
+            .option("multipleSelectionsAllowed", Some(true))
+            .into();
+        let got: Json = serde_json::from_str(&got).unwrap();
+        assert_eq!(json!(["a", "b"]), got["text"]);
+    }
+
+    #[test]
+    fn test_request_without_text() {
+        let got: String = Request::without_text("chooseFolder")
+            .path("defaultLocation", Some("/tmp"))
+            .posix_paths()
+            .into();
+        let got: Json = serde_json::from_str(&got).unwrap();
+        assert_eq!(Json::Null, got["text"]);
+        assert_eq!(json!(["defaultLocation"]), got["paths"]);
+        assert_eq!(json!(true), got["posixPaths"]);
+    }
+
+    #[test]
+    fn test_request_rgb_color() {
+        let got: String = Request::without_text("chooseColor")
+            .option("defaultColor", Some(vec![1.0, 0.5, 0.0]))
+            .rgb_color()
+            .into();
+        let got: Json = serde_json::from_str(&got).unwrap();
+        assert_eq!(json!({"defaultColor": [1.0, 0.5, 0.0]}), got["options"]);
+        assert_eq!(json!(true), got["rgbColor"]);
+    }
+}
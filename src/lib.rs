@@ -0,0 +1,37 @@
+//! Display a notification, dialog or alert via AppleScript or JavaScript for Automation.
+//!
+//! ```no_run
+//! use scptdisplay::Dialog;
+//!
+//! let reply = Dialog::new("Deploy?")
+//!     .buttons(["Abort", "Deploy"])
+//!     .default_button("Deploy")
+//!     .display()
+//!     .unwrap();
+//! println!("{:?}", reply.button);
+//! ```
+pub mod backend;
+pub mod cli;
+mod cmd;
+pub mod color;
+pub mod display;
+pub mod error;
+pub mod format;
+mod jxa;
+mod parse;
+pub mod pick;
+pub mod response;
+mod script;
+pub mod status;
+pub mod voices;
+
+pub use crate::backend::osascript::{Language, Osascript};
+pub use crate::backend::Backend;
+pub use crate::cli::{AlertType, Urgency};
+pub use crate::color::Color;
+pub use crate::display::{
+    Alert, AlertReply, ChooseColor, ChooseFile, ChooseFileName, ChooseFileNameReply, ChooseFolder,
+    ChooseFromList, ChooseFromListReply, Dialog, DialogReply, Notification, NotificationReply, Say,
+};
+pub use crate::error::{Category, ScriptError};
+pub use crate::response::{Data, Response, Voice};
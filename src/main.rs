@@ -1,49 +1,78 @@
-mod cli;
-mod cmd;
-mod parse;
-mod response;
-use crate::cli::Cli;
-use crate::response::Response;
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use log::debug;
-use std::env;
+use scptdisplay::cli::{Cli, Commands};
+use scptdisplay::pick::Lines;
+use scptdisplay::response::{self, Data, Response};
+use scptdisplay::{format, status, voices};
+use std::io;
 use std::process;
 
+/// Run the command by the backends.
+fn dispatch(args: &Cli, command: &Commands) -> Response {
+    match args.backend() {
+        Ok(x) => {
+            let (result, attempts) = x.dispatch(command);
+            Response::from(result).with_attempts(attempts)
+        }
+        Err(x) => Response::from(x),
+    }
+}
+
+/// Choose from the lines read from stdin and print the chosen lines.
+fn pick(args: &Cli, separator: u8) -> Result<Response> {
+    let lines = Lines::read_stdin(separator)?;
+    if lines.is_empty() {
+        return Err(anyhow!("no lines to pick"));
+    }
+    let result = dispatch(args, &lines.command(args.command())?);
+    if let Some(Data::ChooseFromList {
+        items,
+        cancelled: false,
+    }) = result.data()
+    {
+        lines.write(&mut io::stdout().lock(), items)?;
+    }
+    Ok(result)
+}
+
 fn main() {
     env_logger::init();
 
-    let args = Cli::parse();
-    let cli_cmd = args.cmd();
+    // usage errors exit with 1 since 2 means the user pressed the cancel button
+    let args = Cli::try_parse()
+        .and_then(|x| x.validate().map(|_| x))
+        .unwrap_or_else(|err| {
+            let _ = err.print();
+            process::exit(if err.use_stderr() {
+                status::ERROR
+            } else {
+                status::OK
+            })
+        });
+    if args.is_schema() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&response::schema()).unwrap()
+        );
+        return;
+    }
 
     debug!("args: {args:?}");
-    debug!("cmd: {cli_cmd:?}");
 
-    let cmd = process::Command::from(cli_cmd)
-        .stdin(process::Stdio::null())
-        .stdout(process::Stdio::piped())
-        .stderr(process::Stdio::piped())
-        .env_clear()
-        .envs(env::vars())
-        .spawn();
+    if let Commands::Pick { null, .. } = args.command() {
+        let separator = if *null { b'\0' } else { b'\n' };
+        let result = pick(&args, separator).unwrap_or_else(Response::from);
+        if let Some(err) = result.error() {
+            eprintln!("{}", err.message);
+        }
+        process::exit(status::exit_status(&result, &args.exit_code_map()));
+    }
 
-    let result: Response = match cmd {
-        Err(err) => Error::new(err).into(),
-        Ok(cmd) => match cmd.wait_with_output() {
-            Err(err) => Error::new(err).into(),
-            Ok(x) => {
-                if x.status.success() {
-                    Response::from(args.parse_stdout(x.stdout))
-                } else {
-                    let code = x.status.code();
-                    match String::from_utf8(x.stderr) {
-                        Ok(x) => Response::from_err_with_code(anyhow!(x), code),
-                        Err(x) => Response::from_err_with_code(Error::new(x), code),
-                    }
-                }
-            }
-        },
+    let result = match args.command() {
+        Commands::Voices => Response::from(voices::list()),
+        x => dispatch(&args, x),
     };
-
-    println!("{}", serde_json::to_string(&result).unwrap());
+    print!("{}", format::render(&result, args.print(), args.format()));
+    process::exit(status::exit_status(&result, &args.exit_code_map()));
 }
@@ -1,118 +1,501 @@
-use std::collections::HashMap;
+use anyhow::{anyhow, Error, Result};
+use serde_json::{Map, Value as Json};
+use std::fmt;
 
 /// Set of conversions into apple script input.
 pub struct Input;
 
 impl Input {
+    /// Convert into an apple script string literal.
+    ///
+    /// Double quotes, backslashes, tabs and line breaks are escaped.
+    /// Other control characters have no escape sequence in apple script,
+    /// so they are concatenated as `character id N`.
     pub fn quoted(x: &str) -> String {
-        format!("\"{x}\"")
+        let mut parts = Vec::new();
+        let mut s = String::new();
+        for c in x.chars() {
+            match c {
+                '"' => s.push_str("\\\""),
+                '\\' => s.push_str("\\\\"),
+                '\t' => s.push_str("\\t"),
+                '\n' => s.push_str("\\n"),
+                '\r' => s.push_str("\\r"),
+                c if c.is_control() => {
+                    if !s.is_empty() {
+                        parts.push(format!("\"{s}\""));
+                        s.clear();
+                    }
+                    parts.push(format!("(character id {})", c as u32));
+                }
+                c => s.push(c),
+            }
+        }
+        if parts.is_empty() {
+            return format!("\"{s}\"");
+        }
+        if !s.is_empty() {
+            parts.push(format!("\"{s}\""));
+        }
+        if !parts[0].starts_with('"') {
+            // ensure the concatenation yields text
+            parts.insert(0, "\"\"".into());
+        }
+        format!("({})", parts.join(" & "))
     }
+}
 
-    pub fn integer_or_text(x: &str) -> String {
-        if x.parse::<u8>().is_ok() {
-            x.into()
-        } else {
-            Input::quoted(x)
+/// Set of conversions from apple script output.
+pub struct Output;
+
+impl Output {
+    /// Parse the record printed by `osascript -s s` into a json object.
+    pub fn record(x: &str) -> Result<Json> {
+        if x.trim().is_empty() {
+            return Ok(Json::Object(Map::new()));
+        }
+        match Output::value(x)? {
+            Value::List(xs) if xs.is_empty() => Ok(Json::Object(Map::new())),
+            v @ Value::Record(_) => Ok(v.into()),
+            v => Err(anyhow!("not a record: {v}")),
         }
     }
 
-    pub fn apple_script_list<S: Into<String>>(v: Vec<S>) -> String {
-        if v.is_empty() {
-            "{}".into()
-        } else {
-            let x = v
-                .into_iter()
-                .map(|x| {
-                    let s = x.into();
-                    Input::quoted(&s)
-                })
-                .reduce(|acc, x| format!("{acc},{x}"))
-                .unwrap();
-            format!("{{{x}}}")
+    /// Parse the list printed by `osascript -s s` into a json array.
+    ///
+    /// false is kept as is, e.g. choose from list returns false when cancelled.
+    pub fn list(x: &str) -> Result<Json> {
+        match Output::value(x)? {
+            v @ Value::List(_) => Ok(v.into()),
+            Value::Boolean(false) => Ok(Json::Bool(false)),
+            v => Err(anyhow!("not a list: {v}")),
         }
     }
+
+    /// Parse the value printed by `osascript -s s`.
+    pub fn value(x: &str) -> Result<Value> {
+        let mut p = Parser {
+            chars: x.chars().collect(),
+            pos: 0,
+        };
+        let v = p.value()?;
+        p.skip_whitespace();
+        if p.pos < p.chars.len() {
+            return Err(p.error("end of output"));
+        }
+        Ok(v)
+    }
 }
 
-/// Set of conversions from apple script output.
-pub struct Output;
+/// A value printed by apple script in source form.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    List(Vec<Value>),
+    Record(Vec<(String, Value)>),
+    /// `missing value`.
+    Missing,
+    /// Other constants such as enumerations and raw codes.
+    Constant(String),
+}
 
-pub type StringMap = HashMap<String, String>;
+impl From<Value> for Json {
+    fn from(from: Value) -> Self {
+        match from {
+            Value::Text(x) | Value::Constant(x) => Json::String(x),
+            Value::Integer(x) => x.into(),
+            Value::Real(x) => x.into(),
+            Value::Boolean(x) => x.into(),
+            Value::List(xs) => Json::Array(xs.into_iter().map(Json::from).collect()),
+            Value::Record(xs) => Json::Object(xs.into_iter().map(|(k, v)| (k, v.into())).collect()),
+            Value::Missing => Json::Null,
+        }
+    }
+}
 
-impl Output {
-    pub fn record(x: &str) -> StringMap {
-        let xs: Vec<_> = x.trim_end().split(',').collect();
-        let mut m = StringMap::new();
-        for x in xs {
-            if let Some((k, v)) = x.split_once(':') {
-                let k = k.trim_start();
-                m.insert(k.to_string(), v.to_string());
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Text(x) => write!(f, "{}", Input::quoted(x)),
+            Value::Integer(x) => write!(f, "{x}"),
+            Value::Real(x) => write!(f, "{x:?}"),
+            Value::Boolean(x) => write!(f, "{x}"),
+            Value::List(xs) => {
+                let xs: Vec<_> = xs.iter().map(|x| format!("{x}")).collect();
+                write!(f, "{{{}}}", xs.join(", "))
+            }
+            Value::Record(xs) => {
+                let xs: Vec<_> = xs.iter().map(|(k, v)| format!("{k}:{v}")).collect();
+                write!(f, "{{{}}}", xs.join(", "))
             }
+            Value::Missing => write!(f, "missing value"),
+            Value::Constant(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+    fn error(&self, want: &str) -> Error {
+        match self.peek() {
+            Some(c) => anyhow!("expected {want} but found {c:?} at {}", self.pos),
+            None => anyhow!("expected {want} but found end of output"),
+        }
+    }
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("{c:?}")))
+        }
+    }
+    fn value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.list_or_record(),
+            Some('"') => self.text().map(Value::Text),
+            Some('«') => self.raw_code(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c.is_alphabetic() => {
+                let x = self.words();
+                Ok(match x.as_str() {
+                    "true" => Value::Boolean(true),
+                    "false" => Value::Boolean(false),
+                    "missing value" => Value::Missing,
+                    _ => Value::Constant(x),
+                })
+            }
+            _ => Err(self.error("value")),
+        }
+    }
+    /// Read words separated by spaces such as `missing value` or `button returned`.
+    fn words(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == ' ')
+        {
+            self.pos += 1;
+        }
+        let x: String = self.chars[start..self.pos].iter().collect();
+        let trimmed = x.trim_end();
+        self.pos -= x.len() - trimmed.len();
+        trimmed.into()
+    }
+    fn text(&mut self) -> Result<String> {
+        self.expect('"')?;
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("'\"'")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('r') => s.push('\r'),
+                        Some(c) => s.push(c),
+                        None => return Err(self.error("escaped character")),
+                    }
+                    self.pos += 1;
+                }
+                Some(c) => {
+                    s.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+    /// Read a raw code such as `«class abcd»`.
+    fn raw_code(&mut self) -> Result<Value> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            if c == '»' {
+                return Ok(Value::Constant(
+                    self.chars[start..self.pos].iter().collect(),
+                ));
+            }
+        }
+        Err(self.error("'»'"))
+    }
+    fn number(&mut self) -> Result<Value> {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == 'E' || c == 'e')
+        {
+            self.pos += 1;
+            if matches!(self.chars[self.pos - 1], 'E' | 'e')
+                && matches!(self.peek(), Some('+' | '-'))
+            {
+                self.pos += 1;
+            }
+        }
+        let x: String = self.chars[start..self.pos].iter().collect();
+        if let Ok(n) = x.parse::<i64>() {
+            return Ok(Value::Integer(n));
+        }
+        match x.parse::<f64>() {
+            Ok(n) => Ok(Value::Real(n)),
+            Err(_) => Err(anyhow!("invalid number {x:?} at {start}")),
+        }
+    }
+    /// Read a record label, or None if the next is not a label.
+    fn label(&mut self) -> Result<Option<String>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let label = match self.peek() {
+            Some('|') => {
+                self.pos += 1;
+                let mut s = String::new();
+                loop {
+                    match self.peek() {
+                        None => return Err(self.error("'|'")),
+                        Some('|') => break,
+                        Some('\\') => {
+                            self.pos += 1;
+                            if let Some(c) = self.peek() {
+                                s.push(c);
+                            }
+                        }
+                        Some(c) => s.push(c),
+                    }
+                    self.pos += 1;
+                }
+                self.pos += 1;
+                s
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => self.words(),
+            _ => return Ok(None),
+        };
+        self.skip_whitespace();
+        if self.peek() == Some(':') {
+            self.pos += 1;
+            Ok(Some(label))
+        } else {
+            self.pos = start;
+            Ok(None)
+        }
+    }
+    fn list_or_record(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::List(vec![]));
+        }
+        let start = self.pos;
+        let is_record = self.label()?.is_some();
+        self.pos = start;
+        let mut items = vec![];
+        let mut fields = vec![];
+        loop {
+            if is_record {
+                let k = self.label()?.ok_or_else(|| self.error("label"))?;
+                fields.push((k, self.value()?));
+            } else {
+                items.push(self.value()?);
+            }
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error("',' or '}'")),
+            }
+        }
+        if is_record {
+            Ok(Value::Record(fields))
+        } else {
+            Ok(Value::List(items))
         }
-        m
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     macro_rules! test_output_record {
         ($name:ident, $input:expr, $want:expr) => {
             #[test]
             fn $name() {
-                let got = Output::record($input);
+                let got = Output::record($input).unwrap();
                 assert_eq!($want, got);
             }
         };
     }
 
-    test_output_record!(test_record_empty, "", StringMap::new());
+    test_output_record!(test_record_empty, "", json!({}));
+    test_output_record!(test_record_empty_braces, "{}\n", json!({}));
+    test_output_record!(test_record_one, r#"{key:"value"}"#, json!({"key": "value"}));
     test_output_record!(
-        test_record_one,
-        "key:value",
-        StringMap::from([("key".to_string(), "value".to_string())])
+        test_record_two,
+        r#"{key:"value", key 2:"value 2"}"#,
+        json!({"key": "value", "key 2": "value 2"})
     );
     test_output_record!(
-        test_record_two,
-        "key:value, key 2:value 2",
-        StringMap::from([
-            ("key".to_string(), "value".to_string()),
-            ("key 2".to_string(), "value 2".to_string()),
-        ])
+        test_record_dialog,
+        "{button returned:\"Yes, \\\"really\\\": go\", text returned:\"a\\\\b\nc{}|\", gave up:false}\n",
+        json!({
+            "button returned": "Yes, \"really\": go",
+            "text returned": "a\\b\nc{}|",
+            "gave up": false,
+        })
+    );
+    test_output_record!(
+        test_record_bars,
+        r#"{|a:b|:1, |c\|d|:missing value}"#,
+        json!({"a:b": 1, "c|d": null})
+    );
+    test_output_record!(
+        test_record_typed,
+        r#"{n:-1, r:2.5, b:true, l:{1, "x"}, m:{k:caution}}"#,
+        json!({"n": -1, "r": 2.5, "b": true, "l": [1, "x"], "m": {"k": "caution"}})
+    );
+
+    #[test]
+    fn test_record_not_record() {
+        assert!(Output::record(r#""text""#).is_err());
+    }
+
+    macro_rules! test_output_list {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = Output::list($input).unwrap();
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_output_list!(test_list_empty, "{}\n", json!([]));
+    test_output_list!(
+        test_list_items,
+        r#"{"a, b", "c\"d"}"#,
+        json!(["a, b", "c\"d"])
     );
+    test_output_list!(test_list_false, "false\n", json!(false));
+
+    #[test]
+    fn test_list_not_list() {
+        assert!(Output::list("true").is_err());
+    }
 
-    macro_rules! test_input_integer_or_text {
+    macro_rules! test_output_value {
         ($name:ident, $input:expr, $want:expr) => {
             #[test]
             fn $name() {
-                let got = Input::integer_or_text($input);
+                let got = Output::value($input).unwrap();
                 assert_eq!($want, got);
             }
         };
     }
 
-    test_input_integer_or_text!(test_integer_or_text_1, "1", "1");
-    test_input_integer_or_text!(test_integer_or_text_one, "one", "\"one\"");
+    test_output_value!(test_value_text, r#""a\tb\n""#, Value::Text("a\tb\n".into()));
+    test_output_value!(test_value_integer, "-12", Value::Integer(-12));
+    test_output_value!(test_value_real, "1.5", Value::Real(1.5));
+    test_output_value!(test_value_real_exponent, "1.0E+20", Value::Real(1.0e20));
+    test_output_value!(test_value_true, "true", Value::Boolean(true));
+    test_output_value!(test_value_false, " false ", Value::Boolean(false));
+    test_output_value!(test_value_missing, "missing value", Value::Missing);
+    test_output_value!(
+        test_value_constant,
+        "caution",
+        Value::Constant("caution".into())
+    );
+    test_output_value!(
+        test_value_raw_code,
+        "«class abcd»",
+        Value::Constant("«class abcd»".into())
+    );
+    test_output_value!(
+        test_value_nested,
+        r#"{{1, "a"}, {x:{}, y:{true, missing value}}}"#,
+        Value::List(vec![
+            Value::List(vec![Value::Integer(1), Value::Text("a".into())]),
+            Value::Record(vec![
+                ("x".into(), Value::List(vec![])),
+                (
+                    "y".into(),
+                    Value::List(vec![Value::Boolean(true), Value::Missing])
+                ),
+            ]),
+        ])
+    );
+
+    macro_rules! test_output_value_error {
+        ($name:ident, $input:expr) => {
+            #[test]
+            fn $name() {
+                assert!(Output::value($input).is_err());
+            }
+        };
+    }
+
+    test_output_value_error!(test_value_error_empty, "");
+    test_output_value_error!(test_value_error_unterminated_text, r#""abc"#);
+    test_output_value_error!(test_value_error_unterminated_list, "{1, 2");
+    test_output_value_error!(test_value_error_trailing, "1 2");
+    test_output_value_error!(test_value_error_missing_label, "{a:1, 2}");
 
-    macro_rules! test_input_apple_script_list {
+    macro_rules! test_input_quoted {
         ($name:ident, $input:expr, $want:expr) => {
             #[test]
             fn $name() {
-                let got = Input::apple_script_list($input);
+                let got = Input::quoted($input);
                 assert_eq!($want, got);
             }
         };
     }
 
-    test_input_apple_script_list!(
-        test_apple_script_list_empty,
-        Vec::<String>::new(),
-        "{}".to_string()
+    test_input_quoted!(test_quoted_empty, "", r#""""#);
+    test_input_quoted!(test_quoted_plain, "hello", r#""hello""#);
+    test_input_quoted!(test_quoted_unicode, "こんにちは 🍣", r#""こんにちは 🍣""#);
+    test_input_quoted!(test_quoted_quote, r#"say "hi""#, r#""say \"hi\"""#);
+    test_input_quoted!(test_quoted_backslash, r"a\b", r#""a\\b""#);
+    test_input_quoted!(test_quoted_whitespace, "a\tb\nc\rd", r#""a\tb\nc\rd""#);
+    test_input_quoted!(
+        test_quoted_injection,
+        r#"" & (do shell script "id") & ""#,
+        r#""\" & (do shell script \"id\") & \"""#
+    );
+    test_input_quoted!(
+        test_quoted_control,
+        "a\u{7}b",
+        r#"("a" & (character id 7) & "b")"#
     );
-    test_input_apple_script_list!(test_apple_script_list_one, vec!["a"], "{\"a\"}".to_string());
-    test_input_apple_script_list!(
-        test_apple_script_list_two,
-        vec!["a", "b"],
-        "{\"a\",\"b\"}".to_string()
+    test_input_quoted!(
+        test_quoted_control_only,
+        "\u{0}",
+        r#"("" & (character id 0))"#
     );
 }
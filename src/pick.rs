@@ -0,0 +1,185 @@
+use crate::cli::Commands;
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+/// Lines read from stdin to choose from, e.g. `git branch | scptdisplay pick`.
+///
+/// Each line is displayed by a label, the lossy text made unique,
+/// and the chosen labels are mapped back to the lines by their indices,
+/// so that the lines are printed as they were read even if they are not valid UTF-8.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lines {
+    separator: u8,
+    lines: Vec<Vec<u8>>,
+    labels: Vec<String>,
+}
+
+/// The text displayed for the line.
+///
+/// Control characters and line separators are replaced by spaces because a label is a line.
+fn label(x: &[u8]) -> String {
+    String::from_utf8_lossy(x)
+        .chars()
+        .map(|c| {
+            if c.is_control() || c == '\u{2028}' || c == '\u{2029}' {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+impl Lines {
+    /// Split the input by the separator, empty lines are skipped.
+    pub fn new(input: &[u8], separator: u8) -> Lines {
+        let lines: Vec<Vec<u8>> = input
+            .split(|x| *x == separator)
+            .filter(|x| !x.is_empty())
+            .map(Vec::from)
+            .collect();
+        let mut seen = HashSet::new();
+        let labels = lines
+            .iter()
+            .map(|x| {
+                let base = label(x);
+                let mut x = base.clone();
+                let mut n = 1;
+                while !seen.insert(x.clone()) {
+                    n += 1;
+                    x = format!("{base} ({n})");
+                }
+                x
+            })
+            .collect();
+        Lines {
+            separator,
+            lines,
+            labels,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The choose from list command of the pick command.
+    pub fn command(&self, pick: &Commands) -> Result<Commands> {
+        let Commands::Pick {
+            multi,
+            preselect,
+            prompt,
+            title,
+            ..
+        } = pick
+        else {
+            return Err(anyhow!("not pick: {}", pick.kind()));
+        };
+        let default_items = match preselect {
+            Some(r) => self
+                .labels
+                .iter()
+                .zip(&self.lines)
+                .filter(|(_, x)| r.is_match(&String::from_utf8_lossy(x)))
+                .map(|(x, _)| x.clone())
+                .take(if *multi { usize::MAX } else { 1 })
+                .collect(),
+            None => vec![],
+        };
+        Ok(Commands::ChooseFromList {
+            items: self.labels.clone(),
+            prompt: prompt.clone(),
+            title: title.clone(),
+            default_items,
+            ok_button_name: None,
+            cancel_button_name: None,
+            multiple_selections_allowed: *multi,
+            empty_selection_allowed: false,
+        })
+    }
+
+    /// The lines of the chosen labels in the order they were read.
+    pub fn chosen(&self, labels: &[String]) -> Result<Vec<&[u8]>> {
+        let index: HashMap<&str, usize> = self
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.as_str(), i))
+            .collect();
+        let mut xs = labels
+            .iter()
+            .map(|x| {
+                index
+                    .get(x.as_str())
+                    .copied()
+                    .ok_or_else(|| anyhow!("unknown item: {x:?}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        xs.sort_unstable();
+        xs.dedup();
+        Ok(xs.into_iter().map(|i| self.lines[i].as_slice()).collect())
+    }
+
+    /// Write the lines of the chosen labels, each followed by the separator.
+    pub fn write<W: Write>(&self, w: &mut W, labels: &[String]) -> Result<()> {
+        for x in self.chosen(labels)? {
+            w.write_all(x)?;
+            w.write_all(&[self.separator])?;
+        }
+        w.flush()?;
+        Ok(())
+    }
+
+    /// Read the lines from stdin.
+    pub fn read_stdin(separator: u8) -> io::Result<Lines> {
+        let mut input = vec![];
+        io::Read::read_to_end(&mut io::stdin().lock(), &mut input)?;
+        Ok(Lines::new(&input, separator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn test_new() {
+        let got = Lines::new(b"a\n\nb\xff\nc\td\na\n", b'\n');
+        assert_eq!(vec!["a", "b\u{fffd}", "c d", "a (2)"], got.labels);
+    }
+
+    #[test]
+    fn test_chosen() {
+        let lines = Lines::new(b"  main\0* dev\xff\0main", b'\0');
+        let got = lines
+            .chosen(&["main".into(), "* dev\u{fffd}".into()])
+            .unwrap();
+        assert_eq!(vec![b"* dev\xff".as_slice(), b"main".as_slice()], got);
+        assert!(lines.chosen(&["x".into()]).is_err());
+        let mut w = vec![];
+        lines.write(&mut w, &["  main".into()]).unwrap();
+        assert_eq!(b"  main\0".to_vec(), w);
+    }
+
+    #[test]
+    fn test_command() {
+        let cli = Cli::parse_from(["scptdisplay", "pick", "--multi", "--preselect", "^fe"]);
+        let lines = Lines::new(b"main\nfeat/a\nfeat/b\n", b'\n');
+        match lines.command(cli.command()).unwrap() {
+            Commands::ChooseFromList {
+                items,
+                default_items,
+                multiple_selections_allowed,
+                ..
+            } => {
+                assert_eq!(vec!["main", "feat/a", "feat/b"], items);
+                assert_eq!(vec!["feat/a", "feat/b"], default_items);
+                assert!(multiple_selections_allowed);
+            }
+            x => panic!("unexpected {x:?}"),
+        }
+    }
+}
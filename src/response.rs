@@ -1,46 +1,127 @@
+use crate::error::{ProcessError, ScriptError};
 use anyhow::{Error, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::convert::From;
 use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Version of the schema of [Response].
+///
+/// Bumped only by breaking changes such as removing or renaming fields.
+/// Additive changes, new fields and new kinds of data, keep the version,
+/// so consumers should ignore the fields and kinds they do not know.
+pub const VERSION: u32 = 2;
+
+/// The output of scptdisplay.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 pub struct Response {
+    /// Version of the schema, bumped only by breaking changes.
+    version: u32,
     result: Code,
+    /// Exit status of invoked process.
     code: Option<i32>,
-    error: Option<String>,
+    /// Null if result is ok.
+    error: Option<ScriptError>,
+    /// Null if result is error.
     data: Option<Data>,
+    /// Outcomes of the backends that ran the command, in order.
+    #[serde(default)]
+    attempts: Vec<Attempt>,
+}
+
+/// The outcome of a backend.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+pub struct Attempt {
+    /// Name of the backend.
+    pub backend: String,
+    pub result: Code,
+    /// Null if result is ok.
+    pub error: Option<ScriptError>,
+}
+
+impl Attempt {
+    pub fn new<S: Into<String>>(backend: S, result: &Result<Data>) -> Attempt {
+        let (result, error) = match result {
+            Ok(_) => (Code::Ok, None),
+            Err(err) => (Code::Err, Some(script_error(err))),
+        };
+        Attempt {
+            backend: backend.into(),
+            result,
+            error,
+        }
+    }
+}
+
+/// The script error of the error, or an unknown error.
+pub(crate) fn script_error(err: &Error) -> ScriptError {
+    if let Some(x) = err.downcast_ref::<ProcessError>() {
+        return x.error.clone();
+    }
+    match err.downcast_ref::<ScriptError>() {
+        Some(x) => x.clone(),
+        None => ScriptError::unknown(format!("{err}")),
+    }
 }
 
 impl Response {
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+    pub fn result(&self) -> &Code {
+        &self.result
+    }
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+    pub fn error(&self) -> Option<&ScriptError> {
+        self.error.as_ref()
+    }
+    pub fn data(&self) -> Option<&Data> {
+        self.data.as_ref()
+    }
+    pub fn attempts(&self) -> &[Attempt] {
+        &self.attempts
+    }
+    pub fn with_attempts(mut self, xs: Vec<Attempt>) -> Self {
+        self.attempts = xs;
+        self
+    }
     pub fn from_err_with_code(from: Error, code: Option<i32>) -> Self {
+        let from = match from.downcast::<ProcessError>() {
+            Ok(x) => return Response::from_err_with_code(x.error.into(), x.code),
+            Err(x) => x,
+        };
+        let error = match from.downcast::<ScriptError>() {
+            Ok(x) => x,
+            Err(x) => ScriptError::unknown(format!("{x}")),
+        };
         Response {
+            version: VERSION,
             result: Code::Err,
             code,
-            error: Some(format!("{from}")),
+            error: Some(error),
             data: None,
+            attempts: vec![],
         }
     }
 }
 
 impl From<Error> for Response {
     fn from(from: Error) -> Self {
-        Response {
-            result: Code::Err,
-            code: None,
-            error: Some(format!("{from}")),
-            data: None,
-        }
+        Response::from_err_with_code(from, None)
     }
 }
 
 impl From<Data> for Response {
     fn from(from: Data) -> Self {
         Response {
+            version: VERSION,
             result: Code::Ok,
             code: Some(0),
             error: None,
             data: Some(from),
+            attempts: vec![],
         }
     }
 }
@@ -54,11 +135,11 @@ impl From<Result<Data>> for Response {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone, Copy)]
 pub enum Code {
-    #[serde(rename(serialize = "ok"))]
+    #[serde(rename = "ok")]
     Ok,
-    #[serde(rename(serialize = "error"))]
+    #[serde(rename = "error")]
     Err,
 }
 
@@ -72,23 +153,231 @@ impl fmt::Display for Code {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The result of the command, tagged by kind.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum Data {
-    #[serde(rename(serialize = "notification"))]
-    Notification {},
-    #[serde(rename(serialize = "dialog"))]
+    Notification {
+        /// How the user interacted with the notification, e.g. actionClicked, replied, closed and timeout.
+        /// Null if the backend does not wait for the user.
+        activation_type: Option<String>,
+        /// Label of the action invoked, null if no action was invoked.
+        action: Option<String>,
+        /// Text replied, null if the user did not reply.
+        reply: Option<String>,
+    },
     Dialog {
+        /// Raw stdout.
         raw: String,
-        record: HashMap<String, String>,
+        /// Parsed stdout, values keep their types and missing value is null.
+        record: serde_json::Value,
+        /// Text returned.
         text: Option<String>,
+        /// Button returned.
         button: Option<String>,
+        /// If true, no button was returned and the command gave up.
         gave_up: bool,
     },
-    #[serde(rename(serialize = "alert"))]
     Alert {
+        /// Raw stdout.
         raw: String,
-        record: HashMap<String, String>,
+        /// Parsed stdout, values keep their types and missing value is null.
+        record: serde_json::Value,
+        /// Button returned.
         button: Option<String>,
+        /// If true, no button was returned and the command gave up.
         gave_up: bool,
     },
+    ChooseFromList {
+        /// Items chosen, in the order of the list.
+        items: Vec<String>,
+        /// If true, the user pressed the cancel button and items is empty.
+        cancelled: bool,
+    },
+    ChooseFile {
+        /// POSIX paths of the files chosen.
+        paths: Vec<String>,
+    },
+    ChooseFolder {
+        /// POSIX paths of the folders chosen, ending with a slash.
+        paths: Vec<String>,
+    },
+    ChooseFileName {
+        /// POSIX path of the file name chosen.
+        path: String,
+        /// If true, the file already exists.
+        exists: bool,
+    },
+    Color {
+        /// Red, green and blue of the color chosen, from 0 to 65535.
+        components: [u16; 3],
+        /// The color as #rrggbb.
+        hex: String,
+        /// Red, green and blue, from 0 to 255.
+        rgb: [u8; 3],
+    },
+    Say {
+        /// POSIX path of the audio file saved, null if the text was spoken.
+        saved_to: Option<String>,
+    },
+    Voices {
+        voices: Vec<Voice>,
+    },
+}
+
+/// A voice installed for say.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, PartialEq, Clone)]
+pub struct Voice {
+    /// Name of the voice, e.g. Samantha.
+    pub name: String,
+    /// Locale of the voice, e.g. en_US.
+    pub locale: String,
+    /// A sample text spoken by the voice.
+    pub sample: String,
+}
+
+/// The json schema of [Response].
+pub fn schema() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(Response)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    macro_rules! test_round_trip {
+        ($name:ident, $input:expr) => {
+            #[test]
+            fn $name() {
+                let want: Response = $input;
+                let got: Response =
+                    serde_json::from_str(&serde_json::to_string(&want).unwrap()).unwrap();
+                assert_eq!(want, got);
+            }
+        };
+    }
+
+    test_round_trip!(
+        test_round_trip_notification,
+        Data::Notification {
+            activation_type: None,
+            action: None,
+            reply: None
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_dialog,
+        Data::Dialog {
+            raw: "raw".into(),
+            record: json!({"button returned": "OK", "gave up": false}),
+            text: None,
+            button: Some("OK".into()),
+            gave_up: false,
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_choose_from_list,
+        Data::ChooseFromList {
+            items: vec!["a".into(), "b".into()],
+            cancelled: false,
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_choose_file,
+        Data::ChooseFile {
+            paths: vec!["/tmp/a b.txt".into()],
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_choose_file_name,
+        Data::ChooseFileName {
+            path: "/tmp/report.csv".into(),
+            exists: false,
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_color,
+        Data::Color {
+            components: [65535, 32896, 0],
+            hex: "#ff8000".into(),
+            rgb: [255, 128, 0],
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_voices,
+        Data::Voices {
+            voices: vec![Voice {
+                name: "Samantha".into(),
+                locale: "en_US".into(),
+                sample: "Hello! My name is Samantha.".into(),
+            }],
+        }
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_error,
+        Error::from(ScriptError::parse(
+            "0:1: execution error: User canceled. (-128)"
+        ))
+        .into()
+    );
+    test_round_trip!(
+        test_round_trip_attempts,
+        Response::from(Data::Notification {
+            activation_type: None,
+            action: None,
+            reply: None
+        })
+        .with_attempts(vec![
+            Attempt::new("osascript", &Err(ScriptError::unknown("failed").into())),
+            Attempt::new(
+                "tui",
+                &Ok(Data::Notification {
+                    activation_type: None,
+                    action: None,
+                    reply: None
+                })
+            ),
+        ])
+    );
+
+    #[test]
+    fn test_serialize() {
+        let got = serde_json::to_value(Response::from(Data::Alert {
+            raw: "".into(),
+            record: json!({}),
+            button: None,
+            gave_up: true,
+        }))
+        .unwrap();
+        let want = json!({
+            "version": 2,
+            "result": "ok",
+            "code": 0,
+            "error": null,
+            "data": {
+                "kind": "alert",
+                "raw": "",
+                "record": {},
+                "button": null,
+                "gave_up": true,
+            },
+            "attempts": [],
+        });
+        assert_eq!(want, got);
+    }
+
+    #[test]
+    fn test_schema() {
+        let got = schema();
+        assert_eq!("Response", got["title"]);
+        assert!(got["properties"]["version"].is_object());
+    }
 }
@@ -0,0 +1,376 @@
+use crate::parse::Input;
+use std::fmt;
+
+/// An enumerated constant of apple script.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Constant {
+    Informational,
+    Warning,
+    Critical,
+    Stop,
+    Note,
+    Caution,
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Constant::Informational => "informational",
+            Constant::Warning => "warning",
+            Constant::Critical => "critical",
+            Constant::Stop => "stop",
+            Constant::Note => "note",
+            Constant::Caution => "caution",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A handler defined in the script.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Handler {
+    /// The POSIX paths of an alias or a list of aliases, as a list.
+    PosixPaths,
+}
+
+impl Handler {
+    fn name(&self) -> &'static str {
+        match self {
+            Handler::PosixPaths => "posixPaths",
+        }
+    }
+    pub(crate) fn definition(&self) -> &'static str {
+        match self {
+            Handler::PosixPaths => {
+                "on posixPaths(xs)
+    if class of xs is not list then set xs to {xs}
+    set ps to {}
+    repeat with x in xs
+        set end of ps to POSIX path of x
+    end repeat
+    return ps
+end posixPaths"
+            }
+        }
+    }
+}
+
+/// An apple script expression.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Expr {
+    Text(String),
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    List(Vec<Expr>),
+    // no subcommand passes a record yet
+    #[cfg_attr(not(test), allow(dead_code))]
+    Record(Vec<(String, Expr)>),
+    /// `POSIX file` specifier of the path.
+    PosixFile(Box<Expr>),
+    /// Alias of the POSIX path.
+    Alias(Box<Expr>),
+    /// The lines of the text.
+    Paragraphs(Box<Expr>),
+    Constant(Constant),
+    /// The nth item of the arguments of the run handler.
+    Argv(usize),
+    Command(Box<Command>),
+    /// A call of the handler with the argument.
+    Call(Handler, Box<Expr>),
+}
+
+impl Expr {
+    pub fn text<S: Into<String>>(x: S) -> Expr {
+        Expr::Text(x.into())
+    }
+    pub fn posix_file(x: Expr) -> Expr {
+        Expr::PosixFile(Box::new(x))
+    }
+    pub fn alias(x: Expr) -> Expr {
+        Expr::Alias(Box::new(x))
+    }
+    pub fn paragraphs(x: Expr) -> Expr {
+        Expr::Paragraphs(Box::new(x))
+    }
+}
+
+impl From<Command> for Expr {
+    fn from(from: Command) -> Self {
+        Expr::Command(Box::new(from))
+    }
+}
+
+/// Write a record label, enclosed in vertical bars unless it is an identifier.
+fn write_label(f: &mut fmt::Formatter, x: &str) -> fmt::Result {
+    let mut cs = x.chars();
+    let is_identifier = cs
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && cs.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        return write!(f, "{x}");
+    }
+    write!(f, "|")?;
+    for c in x.chars() {
+        match c {
+            '|' | '\\' => write!(f, "\\{c}")?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    write!(f, "|")
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Text(x) => write!(f, "{}", Input::quoted(x)),
+            Expr::Integer(x) => write!(f, "{x}"),
+            Expr::Real(x) => write!(f, "{x}"),
+            Expr::Boolean(x) => write!(f, "{x}"),
+            Expr::List(xs) => {
+                write!(f, "{{")?;
+                for (i, x) in xs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{x}")?;
+                }
+                write!(f, "}}")
+            }
+            Expr::Record(xs) => {
+                write!(f, "{{")?;
+                for (i, (k, v)) in xs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write_label(f, k)?;
+                    write!(f, ":{v}")?;
+                }
+                write!(f, "}}")
+            }
+            Expr::PosixFile(x) => write!(f, "(POSIX file {x})"),
+            Expr::Alias(x) => write!(f, "((POSIX file {x}) as alias)"),
+            Expr::Paragraphs(x) => write!(f, "(paragraphs of {x})"),
+            Expr::Constant(x) => write!(f, "{x}"),
+            Expr::Argv(x) => write!(f, "(item {x} of argv)"),
+            Expr::Command(x) => write!(f, "({x})"),
+            Expr::Call(h, x) => write!(f, "{}({x})", h.name()),
+        }
+    }
+}
+
+/// An apple script command with the direct parameter and labeled parameters.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Command {
+    name: &'static str,
+    direct: Option<Expr>,
+    params: Vec<(&'static str, Expr)>,
+}
+
+impl Command {
+    pub fn new(name: &'static str) -> Command {
+        Command {
+            name,
+            direct: None,
+            params: vec![],
+        }
+    }
+    pub fn direct(mut self, x: Expr) -> Self {
+        self.direct = Some(x);
+        self
+    }
+    pub fn param(mut self, label: &'static str, x: Expr) -> Self {
+        self.params.push((label, x));
+        self
+    }
+    /// Add a labeled parameter if x is not None.
+    pub fn param_opt(self, label: &'static str, x: Option<Expr>) -> Self {
+        match x {
+            Some(x) => self.param(label, x),
+            None => self,
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(x) = &self.direct {
+            write!(f, " {x}")?;
+        }
+        for (k, v) in &self.params {
+            write!(f, " {k} {v}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An apple script program.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Script {
+    run_argv: bool,
+    body: Vec<Expr>,
+    handlers: Vec<Handler>,
+}
+
+impl Script {
+    /// If run_argv, the body is enclosed in the run handler that receives argv.
+    pub fn new(run_argv: bool) -> Script {
+        Script {
+            run_argv,
+            body: vec![],
+            handlers: vec![],
+        }
+    }
+    pub fn push<E: Into<Expr>>(mut self, x: E) -> Self {
+        self.body.push(x.into());
+        self
+    }
+    /// Define the handler after the body.
+    pub fn handler(mut self, x: Handler) -> Self {
+        if !self.handlers.contains(&x) {
+            self.handlers.push(x);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indent = if self.run_argv {
+            writeln!(f, "on run argv")?;
+            "    "
+        } else {
+            ""
+        };
+        for (i, x) in self.body.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            // commands are statements here, no need to parenthesize
+            match x {
+                Expr::Command(c) => write!(f, "{indent}{c}")?,
+                x => write!(f, "{indent}{x}")?,
+            }
+        }
+        if self.run_argv {
+            write!(f, "\nend run")?;
+        }
+        for x in &self.handlers {
+            write!(f, "\n\n{}", x.definition())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_expr {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = format!("{}", $input);
+                assert_eq!($want, got);
+            }
+        };
+    }
+
+    test_expr!(test_expr_text, Expr::text("a\"b"), r#""a\"b""#);
+    test_expr!(test_expr_integer, Expr::Integer(-1), "-1");
+    test_expr!(test_expr_real, Expr::Real(0.25), "0.25");
+    test_expr!(test_expr_boolean, Expr::Boolean(true), "true");
+    test_expr!(test_expr_list_empty, Expr::List(vec![]), "{}");
+    test_expr!(
+        test_expr_list_one,
+        Expr::List(vec![Expr::text("a")]),
+        r#"{"a"}"#
+    );
+    test_expr!(
+        test_expr_list_two,
+        Expr::List(vec![Expr::text("a"), Expr::text("b")]),
+        r#"{"a", "b"}"#
+    );
+    test_expr!(
+        test_expr_record,
+        Expr::Record(vec![
+            ("name".into(), Expr::Integer(1)),
+            ("button returned".into(), Expr::text("OK")),
+            ("a|b".into(), Expr::Boolean(false)),
+            ("_x1".into(), Expr::List(vec![])),
+        ]),
+        r#"{name:1, |button returned|:"OK", |a\|b|:false, _x1:{}}"#
+    );
+    test_expr!(
+        test_expr_record_leading_digit,
+        Expr::Record(vec![("1st".into(), Expr::text("a\\b"))]),
+        r#"{|1st|:"a\\b"}"#
+    );
+    test_expr!(
+        test_expr_posix_file,
+        Expr::posix_file(Expr::text("/tmp/x")),
+        r#"(POSIX file "/tmp/x")"#
+    );
+    test_expr!(
+        test_expr_alias,
+        Expr::alias(Expr::Argv(2)),
+        "((POSIX file (item 2 of argv)) as alias)"
+    );
+    test_expr!(
+        test_expr_paragraphs,
+        Expr::paragraphs(Expr::Argv(2)),
+        "(paragraphs of (item 2 of argv))"
+    );
+    test_expr!(
+        test_expr_constant,
+        Expr::Constant(Constant::Caution),
+        "caution"
+    );
+    test_expr!(
+        test_command,
+        Command::new("display alert")
+            .direct(Expr::text("t"))
+            .param("as", Expr::Constant(Constant::Warning))
+            .param_opt("message", None)
+            .param_opt("giving up after", Some(Expr::Integer(3))),
+        r#"display alert "t" as warning giving up after 3"#
+    );
+    test_expr!(
+        test_command_nested,
+        Expr::from(Command::new("path to").direct(Expr::text("x"))),
+        r#"(path to "x")"#
+    );
+    test_expr!(
+        test_script,
+        Script::new(false).push(Command::new("beep")),
+        "beep"
+    );
+    test_expr!(
+        test_expr_call,
+        Expr::Call(
+            Handler::PosixPaths,
+            Box::new(Command::new("choose file").into())
+        ),
+        "posixPaths((choose file))"
+    );
+    test_expr!(
+        test_script_handler,
+        Script::new(true)
+            .push(Expr::Call(Handler::PosixPaths, Box::new(Expr::Argv(2))))
+            .handler(Handler::PosixPaths)
+            .handler(Handler::PosixPaths),
+        format!(
+            "on run argv\n    posixPaths((item 2 of argv))\nend run\n\n{}",
+            Handler::PosixPaths.definition()
+        )
+    );
+    test_expr!(
+        test_script_argv,
+        Script::new(true)
+            .push(Command::new("display notification").direct(Expr::Argv(2)))
+            .push(Command::new("beep")),
+        "on run argv\n    display notification (item 2 of argv)\n    beep\nend run"
+    );
+}
@@ -0,0 +1,225 @@
+use crate::error::Category;
+use crate::response::{Data, Response};
+use anyhow::{anyhow, Error, Result};
+use std::str::FromStr;
+
+/// Successfully processed.
+pub const OK: i32 = 0;
+/// Failed to process.
+pub const ERROR: i32 = 1;
+/// The user pressed the cancel button.
+pub const USER_CANCELLED: i32 = 2;
+/// No button was returned and the command gave up.
+pub const GAVE_UP: i32 = 3;
+
+/// Exit statuses for the returned buttons, e.g. `Deploy=0,Abort=10`.
+///
+/// Commas and backslashes in the button names are escaped by backslashes.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ExitCodeMap(Vec<(String, i32)>);
+
+impl ExitCodeMap {
+    pub fn get(&self, button: &str) -> Option<i32> {
+        self.0.iter().find(|(k, _)| k == button).map(|(_, v)| *v)
+    }
+}
+
+impl FromStr for ExitCodeMap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut entries = vec![];
+        let mut entry = String::new();
+        let mut cs = s.chars();
+        loop {
+            match cs.next() {
+                Some('\\') => match cs.next() {
+                    Some(c) => entry.push(c),
+                    None => return Err(anyhow!("trailing backslash: {s}")),
+                },
+                Some(',') => entries.push(std::mem::take(&mut entry)),
+                Some(c) => entry.push(c),
+                None => {
+                    entries.push(entry);
+                    break;
+                }
+            }
+        }
+        let mut m = vec![];
+        for x in entries {
+            let Some((k, v)) = x.rsplit_once('=') else {
+                return Err(anyhow!("expected BUTTON=STATUS but got {x:?}"));
+            };
+            // exit statuses are truncated to 8 bits
+            let v: u8 = v
+                .parse()
+                .map_err(|err| anyhow!("invalid status of {k:?}, expected 0 to 255: {err}"))?;
+            m.push((k.to_string(), v.into()));
+        }
+        Ok(ExitCodeMap(m))
+    }
+}
+
+/// Exit status for the response.
+///
+/// Actions invoked for notifications are mapped like buttons.
+pub fn exit_status(response: &Response, buttons: &ExitCodeMap) -> i32 {
+    if let Some(err) = response.error() {
+        return match err.category {
+            Category::UserCancelled => USER_CANCELLED,
+            _ => ERROR,
+        };
+    }
+    let (button, gave_up) = match response.data() {
+        Some(Data::Dialog {
+            button, gave_up, ..
+        })
+        | Some(Data::Alert {
+            button, gave_up, ..
+        }) => (button.as_deref(), *gave_up),
+        Some(Data::Notification {
+            activation_type,
+            action,
+            ..
+        }) => (
+            action.as_deref(),
+            activation_type.as_deref() == Some("timeout"),
+        ),
+        Some(Data::ChooseFromList {
+            cancelled: true, ..
+        }) => return USER_CANCELLED,
+        _ => (None, false),
+    };
+    if gave_up {
+        return GAVE_UP;
+    }
+    button.and_then(|x| buttons.get(x)).unwrap_or(OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ScriptError;
+
+    macro_rules! test_exit_code_map {
+        ($name:ident, $input:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let got = ExitCodeMap::from_str($input).unwrap();
+                let want: Vec<(String, i32)> =
+                    $want.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+                assert_eq!(ExitCodeMap(want), got);
+            }
+        };
+    }
+
+    test_exit_code_map!(test_exit_code_map_one, "Deploy=0", vec![("Deploy", 0)]);
+    test_exit_code_map!(test_exit_code_map_max, "Deploy=255", vec![("Deploy", 255)]);
+    test_exit_code_map!(
+        test_exit_code_map_two,
+        "Deploy=0,Abort=10",
+        vec![("Deploy", 0), ("Abort", 10)]
+    );
+    test_exit_code_map!(
+        test_exit_code_map_escaped,
+        r"Yes\, please=4,a=b=5,\\=6",
+        vec![("Yes, please", 4), ("a=b", 5), ("\\", 6)]
+    );
+
+    macro_rules! test_exit_code_map_error {
+        ($name:ident, $input:expr) => {
+            #[test]
+            fn $name() {
+                assert!(ExitCodeMap::from_str($input).is_err());
+            }
+        };
+    }
+
+    test_exit_code_map_error!(test_exit_code_map_no_status, "Deploy");
+    test_exit_code_map_error!(test_exit_code_map_invalid_status, "Deploy=x");
+    test_exit_code_map_error!(test_exit_code_map_too_large, "Deploy=256");
+    test_exit_code_map_error!(test_exit_code_map_negative, "Deploy=-1");
+    test_exit_code_map_error!(test_exit_code_map_trailing_backslash, "Deploy=1\\");
+
+    fn alert(button: &str, gave_up: bool) -> Response {
+        Data::Alert {
+            raw: "".into(),
+            record: serde_json::Value::Null,
+            button: Some(button.into()),
+            gave_up,
+        }
+        .into()
+    }
+
+    macro_rules! test_exit_status {
+        ($name:ident, $response:expr, $map:expr, $want:expr) => {
+            #[test]
+            fn $name() {
+                let m = ExitCodeMap::from_str($map).unwrap();
+                assert_eq!($want, exit_status(&$response, &m));
+            }
+        };
+    }
+
+    test_exit_status!(
+        test_exit_status_notification,
+        Response::from(Data::Notification {
+            activation_type: None,
+            action: None,
+            reply: None
+        }),
+        "OK=5",
+        OK
+    );
+    test_exit_status!(
+        test_exit_status_notification_action,
+        Response::from(Data::Notification {
+            activation_type: Some("actionClicked".into()),
+            action: Some("No".into()),
+            reply: None
+        }),
+        "No=10",
+        10
+    );
+    test_exit_status!(
+        test_exit_status_notification_timeout,
+        Response::from(Data::Notification {
+            activation_type: Some("timeout".into()),
+            action: None,
+            reply: None
+        }),
+        "No=10",
+        GAVE_UP
+    );
+    test_exit_status!(test_exit_status_ok, alert("OK", false), "Abort=10", OK);
+    test_exit_status!(
+        test_exit_status_mapped,
+        alert("Abort", false),
+        "Abort=10",
+        10
+    );
+    test_exit_status!(test_exit_status_gave_up, alert("", true), "=10", GAVE_UP);
+    test_exit_status!(
+        test_exit_status_error,
+        Response::from(anyhow!("failed")),
+        "OK=5",
+        ERROR
+    );
+    test_exit_status!(
+        test_exit_status_user_cancelled,
+        Response::from(Error::from(ScriptError::parse(
+            "0:20: execution error: User canceled. (-128)"
+        ))),
+        "OK=5",
+        USER_CANCELLED
+    );
+    test_exit_status!(
+        test_exit_status_choose_from_list_cancelled,
+        Response::from(Data::ChooseFromList {
+            items: vec![],
+            cancelled: true
+        }),
+        "OK=5",
+        USER_CANCELLED
+    );
+}
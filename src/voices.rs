@@ -0,0 +1,67 @@
+use crate::error::{ProcessError, ScriptError};
+use crate::response::{Data, Voice};
+use anyhow::Result;
+use log::debug;
+use std::process;
+
+/// List the voices installed for say by `say -v ?`.
+///
+/// If say fails, the error is [ProcessError].
+pub fn list() -> Result<Data> {
+    let mut c = process::Command::new("say");
+    c.args(["-v", "?"]);
+    debug!("cmd: {c:?}");
+    let out = c.stdin(process::Stdio::null()).output()?;
+    if !out.status.success() {
+        return Err(ProcessError {
+            code: out.status.code(),
+            error: ScriptError::unknown(String::from_utf8_lossy(&out.stderr).trim()),
+        }
+        .into());
+    }
+    Ok(Data::Voices {
+        voices: parse(&String::from_utf8(out.stdout)?),
+    })
+}
+
+/// Read the voices printed by `say -v ?`, e.g.
+/// `Samantha            en_US    # Hello! My name is Samantha.`
+fn parse(raw: &str) -> Vec<Voice> {
+    raw.lines()
+        .filter_map(|x| {
+            let (voice, sample) = x.split_once('#')?;
+            let (name, locale) = voice.trim().rsplit_once(char::is_whitespace)?;
+            Some(Voice {
+                name: name.trim().into(),
+                locale: locale.into(),
+                sample: sample.trim().into(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let stdout = "Albert              en_US    # Hello! My name is Albert.
+Eddy (English (UK)) en_GB    # Hello! My name is Eddy.
+Kyoko               ja_JP    # こんにちは、私の名前はKyokoです。
+";
+        let voice = |name: &str, locale: &str, sample: &str| Voice {
+            name: name.into(),
+            locale: locale.into(),
+            sample: sample.into(),
+        };
+        assert_eq!(
+            vec![
+                voice("Albert", "en_US", "Hello! My name is Albert."),
+                voice("Eddy (English (UK))", "en_GB", "Hello! My name is Eddy."),
+                voice("Kyoko", "ja_JP", "こんにちは、私の名前はKyokoです。"),
+            ],
+            parse(stdout)
+        );
+    }
+}